@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+
+use crate::logic::{DamageDealt, RejectedTurn, UnitDied};
+
+fn name_of(names: &Query<&Name>, entity: Entity) -> String {
+    names
+        .get(entity)
+        .map_or_else(|_| format!("{entity:?}"), |name| name.to_string())
+}
+
+fn log_rejected_turns(mut rejected_turns: EventReader<RejectedTurn>, names: Query<&Name>) {
+    for RejectedTurn { turn, reason } in rejected_turns.iter() {
+        warn!(
+            "{} can't take that turn: {reason:?}",
+            name_of(&names, turn.unit)
+        );
+    }
+}
+
+fn log_damage(mut damage_dealt: EventReader<DamageDealt>, names: Query<&Name>) {
+    for &DamageDealt {
+        attacker,
+        target,
+        breakdown,
+        counter,
+    } in damage_dealt.iter()
+    {
+        let verb = match (counter, breakdown.profile.heavy) {
+            (true, _) => "counters",
+            (false, false) => "attacks",
+            (false, true) => "heavily attacks",
+        };
+        let outcome = match (breakdown.hit, breakdown.crit) {
+            (false, _) => "misses".to_string(),
+            (true, false) => format!("deals {} damage", breakdown.damage),
+            (true, true) => format!("crits for {} damage", breakdown.damage),
+        };
+        info!(
+            "{} {verb} {} and {outcome}",
+            name_of(&names, attacker),
+            name_of(&names, target)
+        );
+    }
+}
+
+fn log_deaths(mut unit_died: EventReader<UnitDied>, names: Query<&Name>) {
+    for &UnitDied { unit, position } in unit_died.iter() {
+        info!("{} dies at {position}", name_of(&names, unit));
+    }
+}
+
+pub struct BattleLogPlugin;
+
+impl Plugin for BattleLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((log_rejected_turns, log_damage, log_deaths));
+    }
+}
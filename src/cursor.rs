@@ -1,7 +1,16 @@
 use bevy::prelude::*;
 
+use crate::GRID_SIZE;
+
 #[derive(Resource)]
 pub struct CursorPos(pub Vec2);
+
+impl CursorPos {
+    /// The grid position of the tile under the cursor.
+    pub fn grid_position(&self) -> IVec2 {
+        (self.0 / GRID_SIZE).floor().as_ivec2()
+    }
+}
 impl Default for CursorPos {
     fn default() -> Self {
         // Initialize the cursor pos at some far away place. It will get updated
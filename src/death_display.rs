@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+use crate::{
+    logic::{DeathDrop, Remains, UnitDied},
+    GRID_SIZE,
+};
+
+const FADE_OUT_SECONDS: f32 = 0.6;
+
+#[derive(Component)]
+struct FadeOut(Timer);
+
+fn start_fade_out(mut commands: Commands, mut unit_died: EventReader<UnitDied>) {
+    for &UnitDied { unit, .. } in unit_died.iter() {
+        let Some(mut entity_commands) = commands.get_entity(unit) else { continue };
+        entity_commands.despawn_descendants();
+        entity_commands.insert(FadeOut(Timer::from_seconds(
+            FADE_OUT_SECONDS,
+            TimerMode::Once,
+        )));
+    }
+}
+
+fn fade_out(
+    mut commands: Commands,
+    mut fading: Query<(Entity, &mut FadeOut, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut fade_out, mut sprite) in fading.iter_mut() {
+        fade_out.0.tick(time.delta());
+        sprite.color.set_a(fade_out.0.percent_left());
+        if fade_out.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn add_remains_sprite(mut commands: Commands, remains: Query<(Entity, &Remains), Added<Remains>>) {
+    for (entity, remains) in remains.iter() {
+        let (color, size) = match remains.0 {
+            DeathDrop::Corpse => (Color::rgba(0.3, 0.3, 0.3, 0.8), GRID_SIZE * 0.75),
+            DeathDrop::Loot { .. } => (Color::rgb(0.9, 0.8, 0.2), GRID_SIZE * 0.4),
+        };
+        commands.entity(entity).insert(SpriteBundle {
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 1.5)),
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::new(size, size)),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+    }
+}
+
+pub struct DeathDisplayPlugin;
+
+impl Plugin for DeathDisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems((start_fade_out, fade_out, add_remains_sprite));
+    }
+}
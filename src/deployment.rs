@@ -0,0 +1,289 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_ecs_tilemap::tiles::TilePos;
+
+use crate::{
+    cursor::CursorPos,
+    logic::{
+        DeathDrop, Equipment, Faction, GridPosition, LogicTileParam, MovementType, ReachableInfo,
+        StatusEffects, Unit, UnitLogicBundle, UnitRange, UnitSpeed, UnitStats,
+    },
+    zone::{zone, Zone},
+    TRPGState, GRID_SIZE,
+};
+
+/// Marks a zone the player may place their roster on before the battle starts.
+#[derive(Component, Default)]
+struct DeploymentZone;
+
+#[derive(Bundle, LdtkEntity)]
+struct DeploymentZoneBundle {
+    deployment_zone: DeploymentZone,
+    #[with(zone)]
+    zone: Zone,
+    #[grid_coords]
+    grid_coords: GridCoords,
+}
+
+#[derive(Clone)]
+pub struct RosterUnit {
+    pub name: String,
+    pub unit_stats: UnitStats,
+    pub unit_speed: UnitSpeed,
+    pub movement_type: MovementType,
+    pub unit_range: UnitRange,
+    pub equipment: Equipment,
+}
+
+/// Units the player has yet to place on the map.
+#[derive(Resource)]
+pub struct PlayerRoster(pub Vec<RosterUnit>);
+
+impl Default for PlayerRoster {
+    fn default() -> Self {
+        Self(vec![
+            RosterUnit {
+                name: "Knight".to_string(),
+                unit_stats: UnitStats {
+                    max_hp: 8,
+                    max_initiative: 5.0,
+                    base_atk: 3,
+                    base_armor: 3,
+                    agility: 8,
+                },
+                unit_speed: UnitSpeed(4),
+                movement_type: MovementType::Mounted,
+                unit_range: UnitRange {
+                    valid_ranges: vec![1],
+                },
+                equipment: Equipment::new("Lance", Some("Plate Armor")),
+            },
+            RosterUnit {
+                name: "Archer".to_string(),
+                unit_stats: UnitStats {
+                    max_hp: 5,
+                    max_initiative: 5.0,
+                    base_atk: 3,
+                    base_armor: 2,
+                    agility: 10,
+                },
+                unit_speed: UnitSpeed(5),
+                movement_type: MovementType::Foot,
+                unit_range: UnitRange {
+                    valid_ranges: vec![2],
+                },
+                equipment: Equipment::new("Longbow", Some("Leather Armor")),
+            },
+            RosterUnit {
+                name: "Scout".to_string(),
+                unit_stats: UnitStats {
+                    max_hp: 5,
+                    max_initiative: 5.0,
+                    base_atk: 2,
+                    base_armor: 1,
+                    agility: 13,
+                },
+                unit_speed: UnitSpeed(6),
+                movement_type: MovementType::Foot,
+                unit_range: UnitRange {
+                    valid_ranges: vec![1],
+                },
+                equipment: Equipment::new("Dagger", None),
+            },
+        ])
+    }
+}
+
+/// Marks a unit placed by the player during deployment.
+#[derive(Component)]
+struct Deployed;
+
+fn deployed_unit_bundle(roster_unit: RosterUnit, position: IVec2) -> impl Bundle {
+    (
+        Name::new(roster_unit.name),
+        Deployed,
+        UnitLogicBundle {
+            unit: Unit {
+                initiative: 0.0,
+                current_hp: roster_unit.unit_stats.max_hp,
+            },
+            unit_stats: roster_unit.unit_stats,
+            unit_speed: roster_unit.unit_speed,
+            movement_type: roster_unit.movement_type,
+            unit_range: roster_unit.unit_range,
+            grid_position: GridPosition(position),
+            faction: Faction::Player,
+        },
+        roster_unit.equipment,
+        StatusEffects::default(),
+        DeathDrop::Corpse,
+        SpriteBundle {
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 2.0)),
+            sprite: Sprite {
+                color: Color::rgb(0.5, 0.4, 0.3),
+                custom_size: Some(Vec2::new(GRID_SIZE, GRID_SIZE)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+}
+
+#[derive(SystemParam)]
+struct ValidateDeploymentParam<'w, 's> {
+    zones: Query<'w, 's, (&'static Zone, &'static GridCoords), With<DeploymentZone>>,
+    units: Query<'w, 's, &'static GridPosition, With<Unit>>,
+    logic_tile_param: LogicTileParam<'w, 's>,
+}
+
+impl<'w, 's> ValidateDeploymentParam<'w, 's> {
+    fn validate(&self, pos: IVec2, movement_type: MovementType) -> bool {
+        if !self
+            .zones
+            .iter()
+            .any(|(zone, origin)| zone.contains(origin, pos))
+        {
+            return false;
+        }
+        if !self.logic_tile_param.can_move(pos, movement_type) {
+            return false;
+        }
+        if self.units.iter().any(|unit_pos| unit_pos.0 == pos) {
+            return false;
+        }
+        true
+    }
+}
+
+/// Marks where the next unit in the roster can be placed.
+fn mark_deployable_tiles(
+    validate_deployment_param: ValidateDeploymentParam,
+    mut reachable_info: Query<(&TilePos, &mut ReachableInfo)>,
+    roster: Res<PlayerRoster>,
+) {
+    let next_unit = roster.0.first();
+    for (tile_pos, mut reachable_info) in reachable_info.iter_mut() {
+        let pos = IVec2::new(tile_pos.x as i32, tile_pos.y as i32);
+        let deployable = next_unit.is_some_and(|roster_unit| {
+            validate_deployment_param.validate(pos, roster_unit.movement_type)
+        });
+        if reachable_info.reachable != deployable {
+            reachable_info.reachable = deployable;
+        }
+    }
+}
+
+fn deploy_units(
+    mut commands: Commands,
+    mut roster: ResMut<PlayerRoster>,
+    deployed_units: Query<(Entity, &GridPosition), With<Deployed>>,
+    roster_units: Query<(
+        &Name,
+        &UnitStats,
+        &UnitSpeed,
+        &MovementType,
+        &UnitRange,
+        &Equipment,
+    )>,
+    validate_deployment_param: ValidateDeploymentParam,
+    buttons: Res<Input<MouseButton>>,
+    cursor: Res<CursorPos>,
+) {
+    let pos = cursor.grid_position();
+    if buttons.just_pressed(MouseButton::Left) {
+        let Some(roster_unit) = roster.0.first() else { return };
+        if !validate_deployment_param.validate(pos, roster_unit.movement_type) {
+            return;
+        }
+        let roster_unit = roster.0.remove(0);
+        commands.spawn(deployed_unit_bundle(roster_unit, pos));
+    } else if buttons.just_pressed(MouseButton::Right) {
+        let Some((unit, _)) = deployed_units
+            .iter()
+            .find(|(_, unit_pos)| unit_pos.0 == pos) else { return };
+        let Ok((name, unit_stats, unit_speed, &movement_type, unit_range, equipment)) =
+            roster_units.get(unit) else { return };
+        roster.0.insert(
+            0,
+            RosterUnit {
+                name: name.as_str().to_string(),
+                unit_stats: unit_stats.clone(),
+                unit_speed: unit_speed.clone(),
+                movement_type,
+                unit_range: unit_range.clone(),
+                equipment: equipment.clone(),
+            },
+        );
+        commands.entity(unit).despawn_recursive();
+    }
+}
+
+fn start_battle(
+    mut next_state: ResMut<NextState<TRPGState>>,
+    deployed_units: Query<Entity, With<Deployed>>,
+    keys: Res<Input<KeyCode>>,
+) {
+    if !keys.just_pressed(KeyCode::Return) {
+        return;
+    }
+    if deployed_units.is_empty() {
+        return;
+    }
+    next_state.set(TRPGState::Battle);
+}
+
+pub struct DeploymentPlugin;
+
+impl Plugin for DeploymentPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<DeploymentZoneBundle>("PlayerDeploymentZone")
+            .init_resource::<PlayerRoster>()
+            .add_systems(
+                (mark_deployable_tiles, deploy_units, start_battle)
+                    .in_set(OnUpdate(TRPGState::Deployment)),
+            );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{ecs::system::SystemState, prelude::*};
+    use bevy_ecs_ldtk::prelude::*;
+
+    use crate::{
+        logic::{spawn_test_tiles, GridPosition, MovementType, Unit},
+        zone::zone,
+    };
+
+    use super::{DeploymentZone, ValidateDeploymentParam};
+
+    #[test]
+    fn deployment_needs_a_free_tile_in_a_zone() {
+        let mut world = World::new();
+        spawn_test_tiles(&mut world, UVec2::new(5, 5), &[]);
+        let entity_instance = EntityInstance {
+            width: 32,
+            height: 32,
+            ..default()
+        };
+        world.spawn((
+            DeploymentZone,
+            zone(&entity_instance),
+            GridCoords::new(1, 3),
+        ));
+        world.spawn((
+            Unit {
+                initiative: 0.0,
+                current_hp: 5,
+            },
+            GridPosition(IVec2::new(1, 3)),
+        ));
+
+        let mut state: SystemState<ValidateDeploymentParam> = SystemState::new(&mut world);
+        let validate_deployment_param = state.get(&world);
+        assert!(validate_deployment_param.validate(IVec2::new(2, 2), MovementType::Foot));
+        assert!(!validate_deployment_param.validate(IVec2::new(1, 3), MovementType::Foot));
+        assert!(!validate_deployment_param.validate(IVec2::new(3, 3), MovementType::Foot));
+        assert!(!validate_deployment_param.validate(IVec2::new(1, 1), MovementType::Foot));
+    }
+}
@@ -0,0 +1,169 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_ecs_tilemap::tiles::TilePos;
+use bevy_egui::{
+    egui::{self, Align2},
+    EguiContexts, EguiPlugin,
+};
+
+use crate::{
+    cursor::CursorPos,
+    logic::{
+        in_range, CombatForecastParam, Faction, FactionRelations, GridPosition, LineOfSightParam,
+        ReachableTilesParam, StrikeForecast, Unit, UnitRange,
+    },
+    turn_input::heavy_attack_held,
+    MoveDestination, SelectedUnit, TRPGState,
+};
+
+#[derive(SystemParam)]
+struct HoveredTargetParam<'w, 's> {
+    cursor: Res<'w, CursorPos>,
+    units: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Unit,
+            &'static GridPosition,
+            &'static Faction,
+        ),
+    >,
+    relations: Res<'w, FactionRelations>,
+}
+
+impl<'w, 's> HoveredTargetParam<'w, 's> {
+    /// The living unit under the cursor, if it is hostile to `attacker`.
+    fn get(&self, attacker: Entity) -> Option<(Entity, IVec2)> {
+        let (_, _, _, &attacker_faction) = self.units.get(attacker).ok()?;
+        let hovered = self.cursor.grid_position();
+        self.units
+            .iter()
+            .find(|(_, unit, pos, faction)| {
+                unit.current_hp > 0
+                    && pos.0 == hovered
+                    && self.relations.is_hostile(attacker_faction, **faction)
+            })
+            .map(|(target, ..)| (target, hovered))
+    }
+}
+
+#[derive(SystemParam)]
+struct AttackPositionParam<'w, 's> {
+    units: Query<'w, 's, (&'static GridPosition, &'static UnitRange)>,
+    reachable_tiles_param: ReachableTilesParam<'w, 's>,
+    line_of_sight_param: LineOfSightParam<'w, 's>,
+}
+
+impl<'w, 's> AttackPositionParam<'w, 's> {
+    /// The tile `attacker` would strike `target` from. Once a destination is
+    /// chosen that is the only option; before that, the unit's own tile is
+    /// preferred, then the closest reachable tile in range.
+    fn get(&self, attacker: Entity, target: IVec2, destination: Option<IVec2>) -> Option<IVec2> {
+        let (start, unit_range) = self.units.get(attacker).ok()?;
+        let can_attack_from = |pos: IVec2| {
+            in_range(&unit_range.valid_ranges, pos, target)
+                && self.line_of_sight_param.has_line_of_sight(pos, target)
+        };
+        if let Some(destination) = destination {
+            return can_attack_from(destination).then_some(destination);
+        }
+        if can_attack_from(start.0) {
+            return Some(start.0);
+        }
+        self.reachable_tiles_param
+            .get(attacker)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|TilePos { x, y }| IVec2::new(x as i32, y as i32))
+            .filter(|&pos| can_attack_from(pos))
+            .min_by_key(|&pos| {
+                let distance = (pos - start.0).abs();
+                (distance.x + distance.y, pos.x, pos.y)
+            })
+    }
+}
+
+fn strike_lines(ui: &mut egui::Ui, strike: &StrikeForecast) {
+    ui.label(format!(
+        "Damage: {} ({} on crit)",
+        strike.hit_damage, strike.crit_damage
+    ));
+    ui.label(format!(
+        "Hit: {:.0}%  Crit: {:.0}%",
+        strike.profile.hit_chance * 100.0,
+        strike.profile.crit_chance * 100.0
+    ));
+    let profile = &strike.profile;
+    let mut terrain = Vec::new();
+    if profile.terrain_damage != 0 {
+        terrain.push(format!("{:+} damage", profile.terrain_damage));
+    }
+    if profile.terrain_defense > 0 {
+        terrain.push(format!("+{} defense", profile.terrain_defense));
+    }
+    if profile.terrain_evade != 0.0 {
+        terrain.push(format!("{:+.0}% evade", profile.terrain_evade * 100.0));
+    }
+    if !terrain.is_empty() {
+        ui.label(format!("Terrain: {}", terrain.join(", ")));
+    }
+    ui.label(format!("Expected: {:.1}", strike.expected_damage));
+    if strike.kill_chance > 0.0 {
+        ui.label(format!("Kill chance: {:.0}%", strike.kill_chance * 100.0));
+    }
+}
+
+fn show_forecast_panel(
+    mut contexts: EguiContexts,
+    selected_unit: Res<SelectedUnit>,
+    destination: Option<Res<MoveDestination>>,
+    hovered_target_param: HoveredTargetParam,
+    attack_position_param: AttackPositionParam,
+    combat_forecast_param: CombatForecastParam,
+    keys: Res<Input<KeyCode>>,
+) {
+    let attacker = selected_unit.0;
+    let Some((target, target_pos)) = hovered_target_param.get(attacker) else { return };
+    let destination = destination.map(|destination| destination.0);
+    let Some(from) = attack_position_param.get(attacker, target_pos, destination) else { return };
+    let heavy = heavy_attack_held(&keys);
+    let Some(forecast) = combat_forecast_param.forecast(attacker, from, target, heavy) else { return };
+
+    egui::Window::new("Combat Forecast")
+        .anchor(Align2::RIGHT_TOP, [-8.0, 8.0])
+        .resizable(false)
+        .collapsible(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.strong(if heavy {
+                "Heavy Attack"
+            } else {
+                "Attack (Shift: heavy)"
+            });
+            strike_lines(ui, &forecast.attack);
+            ui.separator();
+            ui.strong("Counter");
+            match &forecast.counter {
+                Some(counter) => strike_lines(ui, counter),
+                None => {
+                    ui.label("None");
+                }
+            }
+        });
+}
+
+/// The forecast follows the player from picking a destination to picking a
+/// target.
+fn choosing_turn(state: Res<State<TRPGState>>) -> bool {
+    matches!(state.0, TRPGState::ChoosingMove | TRPGState::ChoosingAttack)
+}
+
+pub struct ForecastPanelPlugin;
+
+impl Plugin for ForecastPanelPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugin(EguiPlugin);
+        }
+        app.add_system(show_forecast_panel.run_if(choosing_turn));
+    }
+}
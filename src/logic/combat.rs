@@ -0,0 +1,459 @@
+use bevy::{ecs::system::SystemParam, prelude::*, utils::synccell::SyncCell};
+
+use super::{GridPosition, LogicTileParam, Unit, UnitRange, UnitStats, BASE_AGILITY};
+
+/// Chance for a unit of `BASE_AGILITY` to hit another on open ground.
+const BASE_HIT_CHANCE: f32 = 0.9;
+/// How much each point of agility above `BASE_AGILITY` adds to the unit's
+/// accuracy and to its evasion.
+const HIT_CHANCE_PER_AGILITY: f32 = 0.02;
+const BASE_CRIT_CHANCE: f32 = 0.05;
+const CRIT_MULTIPLIER: u32 = 2;
+/// Extra attack power of a heavy attack.
+const HEAVY_ATTACK_BONUS: u32 = 2;
+const MIN_DAMAGE: u32 = 1;
+
+/// Source of randomness for attack rolls. Set `TRPG_SEED`, or insert a seeded
+/// one, to make battles reproducible.
+#[derive(Resource)]
+pub struct CombatRng(SyncCell<fastrand::Rng>);
+
+impl CombatRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(SyncCell::new(fastrand::Rng::with_seed(seed)))
+    }
+}
+
+impl Default for CombatRng {
+    fn default() -> Self {
+        std::env::var("TRPG_SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .map_or_else(|| Self(SyncCell::new(fastrand::Rng::new())), Self::seeded)
+    }
+}
+
+/// What the tile a unit stands on does to the strikes it deals and takes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TerrainModifiers {
+    pub defense_bonus: u32,
+    pub evade_bonus: f32,
+    pub damage_bonus: i32,
+    pub melee_damage_bonus: i32,
+}
+
+/// Everything that goes into a single strike, before any dice are rolled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AttackProfile {
+    pub atk: u32,
+    pub armor: u32,
+    pub terrain_defense: u32,
+    /// Damage added or taken away by the attacker's terrain.
+    pub terrain_damage: i32,
+    /// The attacker's chance to hit before the defender evades anything.
+    pub accuracy: f32,
+    /// How much the defender's agility lowered `hit_chance`.
+    pub evade: f32,
+    /// How much the defender's terrain lowered `hit_chance`.
+    pub terrain_evade: f32,
+    pub hit_chance: f32,
+    pub crit_chance: f32,
+    /// Adds `HEAVY_ATTACK_BONUS` to `atk`.
+    pub heavy: bool,
+}
+
+impl AttackProfile {
+    pub fn new(attacker: &Combatant, defender: &Combatant) -> Self {
+        let distance = (defender.position - attacker.position).abs();
+        let melee = distance.x + distance.y == 1;
+        let terrain_damage = attacker.terrain.damage_bonus
+            + if melee {
+                attacker.terrain.melee_damage_bonus
+            } else {
+                0
+            };
+        let accuracy = BASE_HIT_CHANCE + agility_bonus(attacker.stats);
+        let evade = agility_bonus(defender.stats);
+        Self {
+            atk: attacker.stats.base_atk,
+            armor: defender.stats.base_armor,
+            terrain_defense: defender.terrain.defense_bonus,
+            terrain_damage,
+            accuracy,
+            evade,
+            terrain_evade: defender.terrain.evade_bonus,
+            hit_chance: (accuracy - evade - defender.terrain.evade_bonus).clamp(0.0, 1.0),
+            crit_chance: BASE_CRIT_CHANCE,
+            heavy: false,
+        }
+    }
+
+    /// Damage of a regular hit.
+    pub fn hit_damage(&self) -> u32 {
+        let atk = self.atk + if self.heavy { HEAVY_ATTACK_BONUS } else { 0 };
+        ((atk as i32 + self.terrain_damage).max(0) as u32)
+            .saturating_sub(self.armor + self.terrain_defense)
+            .max(MIN_DAMAGE)
+    }
+
+    /// Damage of a critical hit.
+    pub fn crit_damage(&self) -> u32 {
+        self.hit_damage() * CRIT_MULTIPLIER
+    }
+
+    /// What this strike is expected to do to a defender with `defender_hp` left.
+    pub fn forecast(&self, defender_hp: u32) -> StrikeForecast {
+        let hit_damage = self.hit_damage();
+        let crit_damage = self.crit_damage();
+        let kill_chance = if hit_damage >= defender_hp {
+            self.hit_chance
+        } else if crit_damage >= defender_hp {
+            self.hit_chance * self.crit_chance
+        } else {
+            0.0
+        };
+        StrikeForecast {
+            profile: *self,
+            hit_damage,
+            crit_damage,
+            expected_damage: self.hit_chance
+                * ((1.0 - self.crit_chance) * hit_damage as f32
+                    + self.crit_chance * crit_damage as f32),
+            kill_chance,
+        }
+    }
+
+    pub fn roll(&self, rng: &mut CombatRng) -> DamageBreakdown {
+        let hit = rng.0.get().f32() < self.hit_chance;
+        let crit = hit && rng.0.get().f32() < self.crit_chance;
+        let damage = match (hit, crit) {
+            (false, _) => 0,
+            (true, false) => self.hit_damage(),
+            (true, true) => self.crit_damage(),
+        };
+        DamageBreakdown {
+            profile: *self,
+            hit,
+            crit,
+            damage,
+        }
+    }
+}
+
+/// The outcome of a single strike.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DamageBreakdown {
+    pub profile: AttackProfile,
+    pub hit: bool,
+    pub crit: bool,
+    pub damage: u32,
+}
+
+/// What a strike is expected to do, without rolling any dice.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrikeForecast {
+    pub profile: AttackProfile,
+    pub hit_damage: u32,
+    pub crit_damage: u32,
+    pub expected_damage: f32,
+    pub kill_chance: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CombatForecast {
+    pub attack: StrikeForecast,
+    /// The defender's answer, should it survive and have the attacker in range.
+    pub counter: Option<StrikeForecast>,
+}
+
+/// Accuracy and evasion a unit gains, or loses, from its agility.
+fn agility_bonus(unit_stats: &UnitStats) -> f32 {
+    (unit_stats.agility as f32 - BASE_AGILITY as f32) * HIT_CHANCE_PER_AGILITY
+}
+
+/// A unit as seen by the combat math.
+pub struct Combatant<'a> {
+    pub stats: &'a UnitStats,
+    pub hp: u32,
+    pub position: IVec2,
+    pub ranges: &'a [u32],
+    pub terrain: TerrainModifiers,
+}
+
+pub fn in_range(ranges: &[u32], from: IVec2, to: IVec2) -> bool {
+    let distance = (to - from).abs();
+    ranges.contains(&((distance.x + distance.y) as u32))
+}
+
+/// Whether `defender` strikes back at an attacker standing on `attacker_position`.
+pub fn can_counter(defender: &Combatant, attacker_position: IVec2) -> bool {
+    in_range(defender.ranges, defender.position, attacker_position)
+}
+
+pub fn forecast_combat(attacker: &Combatant, defender: &Combatant, heavy: bool) -> CombatForecast {
+    let attack = AttackProfile {
+        heavy,
+        ..AttackProfile::new(attacker, defender)
+    }
+    .forecast(defender.hp);
+    let counter = (attack.kill_chance < 1.0 && can_counter(defender, attacker.position))
+        .then(|| AttackProfile::new(defender, attacker).forecast(attacker.hp));
+    CombatForecast { attack, counter }
+}
+
+#[derive(SystemParam)]
+pub struct CombatForecastParam<'w, 's> {
+    units: Query<
+        'w,
+        's,
+        (
+            &'static Unit,
+            &'static UnitStats,
+            &'static UnitRange,
+            &'static GridPosition,
+        ),
+    >,
+    logic_tile_param: LogicTileParam<'w, 's>,
+}
+
+impl<'w, 's> CombatForecastParam<'w, 's> {
+    /// Forecasts `attacker` striking `target` after moving to `from`.
+    pub fn forecast(
+        &self,
+        attacker: Entity,
+        from: IVec2,
+        target: Entity,
+        heavy: bool,
+    ) -> Option<CombatForecast> {
+        let (attacker_unit, attacker_stats, attacker_range, _) = self.units.get(attacker).ok()?;
+        let (target_unit, target_stats, target_range, target_pos) = self.units.get(target).ok()?;
+        Some(forecast_combat(
+            &Combatant {
+                stats: attacker_stats,
+                hp: attacker_unit.current_hp,
+                position: from,
+                ranges: &attacker_range.valid_ranges,
+                terrain: self.logic_tile_param.terrain_modifiers(from),
+            },
+            &Combatant {
+                stats: target_stats,
+                hp: target_unit.current_hp,
+                position: target_pos.0,
+                ranges: &target_range.valid_ranges,
+                terrain: self.logic_tile_param.terrain_modifiers(target_pos.0),
+            },
+            heavy,
+        ))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DamageDealt {
+    pub attacker: Entity,
+    pub target: Entity,
+    pub breakdown: DamageBreakdown,
+    /// Whether this was a defender striking back.
+    pub counter: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use crate::logic::{UnitStats, BASE_AGILITY};
+
+    use super::{forecast_combat, AttackProfile, CombatRng, Combatant, TerrainModifiers};
+
+    fn profile(atk: u32, armor: u32, hit_chance: f32, crit_chance: f32) -> AttackProfile {
+        AttackProfile {
+            atk,
+            armor,
+            terrain_defense: 1,
+            terrain_damage: 0,
+            accuracy: hit_chance,
+            evade: 0.0,
+            terrain_evade: 0.0,
+            hit_chance,
+            crit_chance,
+            heavy: false,
+        }
+    }
+
+    #[test]
+    fn armor_and_terrain_mitigate_damage() {
+        let breakdown = profile(6, 2, 1.0, 0.0).roll(&mut CombatRng::seeded(0));
+        assert!(breakdown.hit);
+        assert!(!breakdown.crit);
+        assert_eq!(breakdown.damage, 3);
+    }
+
+    #[test]
+    fn hits_deal_at_least_minimum_damage() {
+        let breakdown = profile(1, 5, 1.0, 0.0).roll(&mut CombatRng::seeded(0));
+        assert_eq!(breakdown.damage, 1);
+    }
+
+    #[test]
+    fn crits_multiply_damage() {
+        let breakdown = profile(6, 2, 1.0, 1.0).roll(&mut CombatRng::seeded(0));
+        assert!(breakdown.crit);
+        assert_eq!(breakdown.damage, 6);
+    }
+
+    #[test]
+    fn misses_deal_no_damage() {
+        let breakdown = profile(6, 2, 0.0, 1.0).roll(&mut CombatRng::seeded(0));
+        assert!(!breakdown.hit);
+        assert!(!breakdown.crit);
+        assert_eq!(breakdown.damage, 0);
+    }
+
+    #[test]
+    fn heavy_attacks_hit_harder() {
+        let regular = profile(6, 2, 1.0, 0.0);
+        let heavy = AttackProfile {
+            heavy: true,
+            ..regular
+        };
+        assert!(heavy.hit_damage() > regular.hit_damage());
+    }
+
+    #[test]
+    fn same_seed_rolls_the_same() {
+        let profile = profile(6, 2, 0.5, 0.5);
+        let mut a = CombatRng::seeded(42);
+        let mut b = CombatRng::seeded(42);
+        for _ in 0..20 {
+            assert_eq!(profile.roll(&mut a), profile.roll(&mut b));
+        }
+    }
+
+    fn stats(base_atk: u32, base_armor: u32) -> UnitStats {
+        UnitStats {
+            max_hp: 10,
+            max_initiative: 5.0,
+            base_atk,
+            base_armor,
+            agility: BASE_AGILITY,
+        }
+    }
+
+    #[test]
+    fn forecast_predicts_kills_and_counters() {
+        let attacker_stats = stats(6, 1);
+        let defender_stats = stats(3, 2);
+        let attacker = Combatant {
+            stats: &attacker_stats,
+            hp: 10,
+            position: IVec2::new(0, 0),
+            ranges: &[1],
+            terrain: TerrainModifiers::default(),
+        };
+        let defender = Combatant {
+            stats: &defender_stats,
+            hp: 6,
+            position: IVec2::new(1, 0),
+            ranges: &[1],
+            terrain: TerrainModifiers::default(),
+        };
+        let forecast = forecast_combat(&attacker, &defender, false);
+        assert_eq!(forecast.attack.hit_damage, 4);
+        assert_eq!(forecast.attack.crit_damage, 8);
+        assert_eq!(
+            forecast.attack.kill_chance,
+            forecast.attack.profile.hit_chance * forecast.attack.profile.crit_chance
+        );
+        let counter = forecast
+            .counter
+            .expect("defender has the attacker in range");
+        assert_eq!(counter.hit_damage, 2);
+        assert_eq!(counter.kill_chance, 0.0);
+    }
+
+    #[test]
+    fn forecast_has_no_counter_out_of_range() {
+        let attacker_stats = stats(6, 1);
+        let defender_stats = stats(3, 2);
+        let attacker = Combatant {
+            stats: &attacker_stats,
+            hp: 10,
+            position: IVec2::new(0, 0),
+            ranges: &[2],
+            terrain: TerrainModifiers::default(),
+        };
+        let defender = Combatant {
+            stats: &defender_stats,
+            hp: 6,
+            position: IVec2::new(2, 0),
+            ranges: &[1],
+            terrain: TerrainModifiers::default(),
+        };
+        assert_eq!(forecast_combat(&attacker, &defender, false).counter, None);
+    }
+
+    #[test]
+    fn terrain_modifies_strikes() {
+        let attacker_stats = stats(6, 1);
+        let defender_stats = stats(3, 2);
+        let attacker = Combatant {
+            stats: &attacker_stats,
+            hp: 10,
+            position: IVec2::new(0, 0),
+            ranges: &[1],
+            terrain: TerrainModifiers {
+                melee_damage_bonus: -1,
+                ..Default::default()
+            },
+        };
+        let defender = Combatant {
+            stats: &defender_stats,
+            hp: 10,
+            position: IVec2::new(1, 0),
+            ranges: &[1],
+            terrain: TerrainModifiers {
+                defense_bonus: 1,
+                evade_bonus: 0.2,
+                ..Default::default()
+            },
+        };
+        let profile = AttackProfile::new(&attacker, &defender);
+        assert_eq!(profile.hit_damage(), 2);
+        assert_eq!(profile.terrain_evade, 0.2);
+        assert!(profile.hit_chance < AttackProfile::new(&defender, &attacker).hit_chance);
+
+        // The melee penalty only applies next to the defender.
+        let ranged_attacker = Combatant {
+            position: IVec2::new(3, 0),
+            ranges: &[2],
+            ..attacker
+        };
+        let ranged_profile = AttackProfile::new(&ranged_attacker, &defender);
+        assert_eq!(ranged_profile.terrain_damage, 0);
+        assert_eq!(ranged_profile.hit_damage(), profile.hit_damage() + 1);
+    }
+
+    #[test]
+    fn agility_raises_hit_and_evade_chance() {
+        let average_stats = stats(3, 2);
+        let agile_stats = UnitStats {
+            agility: BASE_AGILITY + 5,
+            ..stats(3, 2)
+        };
+        let combatant = |stats, position| Combatant {
+            stats,
+            hp: 10,
+            position,
+            ranges: &[1],
+            terrain: TerrainModifiers::default(),
+        };
+        let average = combatant(&average_stats, IVec2::new(0, 0));
+        let agile = combatant(&agile_stats, IVec2::new(1, 0));
+        let even = AttackProfile::new(&average, &average).hit_chance;
+        let by_agile = AttackProfile::new(&agile, &average);
+        let at_agile = AttackProfile::new(&average, &agile);
+        assert!(by_agile.accuracy > at_agile.accuracy);
+        assert!(at_agile.evade > by_agile.evade);
+        assert!(by_agile.hit_chance > even);
+        assert!(at_agile.hit_chance < even);
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+
+use super::{GridPosition, Unit, UnitLogicBundle};
+
+/// What a unit leaves behind on its tile when it dies.
+#[derive(Component, Reflect, Clone, Debug, PartialEq)]
+pub enum DeathDrop {
+    Corpse,
+    Loot { item: String },
+}
+
+/// A corpse or loot left on the map by a dead unit.
+#[derive(Component, Reflect)]
+pub struct Remains(pub DeathDrop);
+
+#[derive(Clone, Copy)]
+pub struct UnitDied {
+    pub unit: Entity,
+    pub position: IVec2,
+}
+
+/// Strips dead units of their logic components, so they no longer gain
+/// initiative, take turns or block movement, and leaves their remains behind.
+/// The entity itself is left for the front-end to despawn.
+pub(super) fn kill_units(
+    mut commands: Commands,
+    units: Query<(Entity, &Unit, &GridPosition, Option<&DeathDrop>)>,
+    mut unit_died: EventWriter<UnitDied>,
+) {
+    for (entity, unit, pos, death_drop) in units.iter() {
+        if unit.current_hp > 0 {
+            continue;
+        }
+        commands
+            .entity(entity)
+            .remove::<(UnitLogicBundle, DeathDrop)>();
+        if let Some(death_drop) = death_drop {
+            commands.spawn((
+                Name::new("Remains"),
+                Remains(death_drop.clone()),
+                GridPosition(pos.0),
+            ));
+        }
+        unit_died.send(UnitDied {
+            unit: entity,
+            position: pos.0,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{
+        ecs::{event::Events, system::SystemState},
+        prelude::*,
+    };
+    use bevy_ecs_tilemap::tiles::TilePos;
+
+    use crate::logic::{
+        spawn_test_tiles, Faction, FactionRelations, GridPosition, MovementType,
+        ReachableTilesParam, Unit, UnitLogicBundle, UnitRange, UnitSpeed, UnitStats, BASE_AGILITY,
+    };
+
+    use super::{kill_units, DeathDrop, Remains, UnitDied};
+
+    fn spawn_unit(app: &mut App, position: IVec2, faction: Faction, current_hp: u32) -> Entity {
+        app.world
+            .spawn(UnitLogicBundle {
+                unit: Unit {
+                    initiative: 0.0,
+                    current_hp,
+                },
+                unit_stats: UnitStats {
+                    max_hp: 5,
+                    max_initiative: 5.0,
+                    base_atk: 3,
+                    base_armor: 2,
+                    agility: BASE_AGILITY,
+                },
+                unit_range: UnitRange {
+                    valid_ranges: vec![1],
+                },
+                unit_speed: UnitSpeed(5),
+                movement_type: MovementType::Foot,
+                grid_position: GridPosition(position),
+                faction,
+            })
+            .id()
+    }
+
+    #[test]
+    fn dead_units_leave_the_battle_and_drop_their_remains() {
+        let mut app = App::new();
+        app.init_resource::<FactionRelations>()
+            .add_event::<UnitDied>()
+            .add_system(kill_units);
+        spawn_test_tiles(&mut app.world, UVec2::new(3, 1), &[]);
+        let survivor = spawn_unit(&mut app, IVec2::new(0, 0), Faction::Player, 5);
+        let dead = spawn_unit(&mut app, IVec2::new(1, 0), Faction::Enemy, 0);
+        let loot = DeathDrop::Loot {
+            item: "Boss Treasure".to_string(),
+        };
+        app.world.entity_mut(dead).insert(loot.clone());
+        app.update();
+
+        let events = app.world.resource::<Events<UnitDied>>();
+        let died: Vec<_> = events
+            .get_reader()
+            .iter(events)
+            .map(|unit_died| (unit_died.unit, unit_died.position))
+            .collect();
+        assert_eq!(died, vec![(dead, IVec2::new(1, 0))]);
+
+        // Without its logic components the unit no longer gains initiative.
+        assert!(app.world.get::<Unit>(dead).is_none());
+        assert!(app.world.get::<DeathDrop>(dead).is_none());
+
+        // Nor does it block the way past its tile.
+        let mut reachable_tiles_state: SystemState<ReachableTilesParam> =
+            SystemState::new(&mut app.world);
+        let reachable_tiles = reachable_tiles_state.get(&app.world).get(survivor).unwrap();
+        assert!(reachable_tiles.contains(&TilePos::new(1, 0)));
+        assert!(reachable_tiles.contains(&TilePos::new(2, 0)));
+
+        let remains: Vec<_> = app
+            .world
+            .query::<(&Remains, &GridPosition)>()
+            .iter(&app.world)
+            .map(|(remains, pos)| (remains.0.clone(), pos.0))
+            .collect();
+        assert_eq!(remains, vec![(loot, IVec2::new(1, 0))]);
+    }
+}
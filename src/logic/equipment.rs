@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+
+/// The items a unit carries into battle.
+#[derive(Component, Reflect, Clone, Debug, Default)]
+pub struct Equipment {
+    pub weapon: Option<String>,
+    pub armor: Option<String>,
+}
+
+impl Equipment {
+    pub fn new(weapon: &str, armor: Option<&str>) -> Self {
+        Self {
+            weapon: Some(weapon.to_string()),
+            armor: armor.map(str::to_string),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::TRPGState;
+
+use super::Unit;
+
+#[derive(Component, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Faction {
+    #[default]
+    Player,
+    Enemy,
+    Neutral,
+    AlliedAi,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Relation {
+    Allied,
+    Neutral,
+    Hostile,
+}
+
+/// How each pair of factions treats each other. Pairs missing from the table
+/// are neutral, and a faction is always allied with itself.
+#[derive(Resource)]
+pub struct FactionRelations(HashMap<(Faction, Faction), Relation>);
+
+impl Default for FactionRelations {
+    fn default() -> Self {
+        let mut relations = Self(HashMap::new());
+        relations.set(Faction::Player, Faction::Enemy, Relation::Hostile);
+        relations.set(Faction::Player, Faction::AlliedAi, Relation::Allied);
+        relations.set(Faction::Enemy, Faction::AlliedAi, Relation::Hostile);
+        relations
+    }
+}
+
+impl FactionRelations {
+    pub fn set(&mut self, a: Faction, b: Faction, relation: Relation) {
+        self.0.insert((a, b), relation);
+        self.0.insert((b, a), relation);
+    }
+
+    pub fn get(&self, a: Faction, b: Faction) -> Relation {
+        if a == b {
+            return Relation::Allied;
+        }
+        self.0.get(&(a, b)).copied().unwrap_or(Relation::Neutral)
+    }
+
+    pub fn is_hostile(&self, a: Faction, b: Faction) -> bool {
+        self.get(a, b) == Relation::Hostile
+    }
+
+    pub fn is_allied(&self, a: Faction, b: Faction) -> bool {
+        self.get(a, b) == Relation::Allied
+    }
+}
+
+/// How many enemies have yet to arrive as reinforcements. The battle can't be
+/// won while any have.
+#[derive(Resource, Default)]
+pub struct PendingReinforcements(pub usize);
+
+fn check_victory(
+    units: Query<(&Unit, &Faction)>,
+    relations: Res<FactionRelations>,
+    pending_reinforcements: Res<PendingReinforcements>,
+    mut next_state: ResMut<NextState<TRPGState>>,
+) {
+    let alive = || units.iter().filter(|(unit, _)| unit.current_hp > 0);
+    if !alive().any(|(_, faction)| *faction == Faction::Player) {
+        info!("All player units are down");
+        next_state.set(TRPGState::Defeat);
+    } else if pending_reinforcements.0 == 0
+        && !alive().any(|(_, faction)| relations.is_hostile(Faction::Player, *faction))
+    {
+        info!("No hostile units remain");
+        next_state.set(TRPGState::Victory);
+    }
+}
+
+pub struct FactionPlugin;
+
+impl Plugin for FactionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FactionRelations>()
+            .init_resource::<PendingReinforcements>()
+            .add_system(check_victory.in_set(OnUpdate(TRPGState::Battle)))
+            .register_type::<Faction>();
+    }
+}
@@ -1,14 +1,34 @@
+use std::cmp::Reverse;
+
 use bevy::{ecs::system::SystemParam, prelude::*};
 
 use bevy_ecs_tilemap::tiles::TilePos;
 
 use crate::TRPGState;
 
+pub use self::combat::*;
+pub use self::death::*;
+pub use self::equipment::*;
+pub use self::faction::*;
+pub use self::movement::*;
 pub use self::reachable::*;
+pub use self::sight::*;
+pub use self::status::*;
+pub use self::terrain::*;
 pub use self::tile::*;
+pub use self::turn_order::*;
 
+mod combat;
+mod death;
+mod equipment;
+mod faction;
+mod movement;
 mod reachable;
+mod sight;
+mod status;
+mod terrain;
 mod tile;
+mod turn_order;
 
 #[derive(Deref, Component, Reflect)]
 pub struct GridPosition(pub IVec2);
@@ -19,20 +39,67 @@ pub struct Unit {
     pub current_hp: u32,
 }
 
-#[derive(Component, Reflect)]
+impl Unit {
+    /// Whether the unit's initiative is full, so it may take its turn.
+    pub fn is_ready(&self, unit_stats: &UnitStats) -> bool {
+        self.initiative == unit_stats.max_initiative
+    }
+
+    /// Logical ticks until the unit's initiative is full.
+    pub fn ticks_until_ready(&self, unit_stats: &UnitStats) -> u64 {
+        unit_stats.ticks_to_fill(self.initiative)
+    }
+}
+
+/// The order in which units get to act: the fewest ticks until ready first,
+/// then the higher agility, then the lower entity index. Indices are reused
+/// after despawns, so the last tie-break is only stable, not spawn order.
+pub fn turn_order_key(
+    entity: Entity,
+    unit: &Unit,
+    unit_stats: &UnitStats,
+) -> (u64, Reverse<u32>, u32) {
+    (
+        unit.ticks_until_ready(unit_stats),
+        Reverse(unit_stats.agility),
+        entity.index(),
+    )
+}
+
+#[derive(Component, Reflect, Clone)]
 pub struct UnitStats {
     pub max_hp: u32,
     pub max_initiative: f32,
     pub base_atk: u32,
     pub base_armor: u32,
+    /// How quickly initiative fills up. `BASE_AGILITY` is one initiative per
+    /// second.
+    pub agility: u32,
 }
 
-#[derive(Component, Reflect)]
+pub const BASE_AGILITY: u32 = 10;
+
+impl UnitStats {
+    pub fn initiative_per_tick(&self) -> f32 {
+        self.agility as f32 / BASE_AGILITY as f32 * TICK_SECONDS
+    }
+
+    /// Logical ticks until initiative fills up, starting from `initiative`.
+    pub fn ticks_to_fill(&self, initiative: f32) -> u64 {
+        let per_tick = self.initiative_per_tick();
+        if per_tick <= 0.0 {
+            return u64::MAX;
+        }
+        ((self.max_initiative - initiative) / per_tick).ceil() as u64
+    }
+}
+
+#[derive(Component, Reflect, Clone)]
 pub struct UnitRange {
     pub valid_ranges: Vec<u32>,
 }
 
-#[derive(Component, Reflect)]
+#[derive(Component, Reflect, Clone)]
 pub struct UnitSpeed(pub u32);
 
 #[derive(Bundle)]
@@ -41,20 +108,106 @@ pub struct UnitLogicBundle {
     pub unit_stats: UnitStats,
     pub unit_range: UnitRange,
     pub unit_speed: UnitSpeed,
+    pub movement_type: MovementType,
     pub grid_position: GridPosition,
+    pub faction: Faction,
+}
+
+/// Length of a logical tick, in seconds of real time and of initiative at
+/// `BASE_AGILITY`.
+pub const TICK_SECONDS: f32 = 0.1;
+
+/// Upper bound on how far turn-based mode jumps ahead, in case no player unit
+/// can ever become ready.
+const MAX_JUMP_TICKS: u32 = 10_000;
+
+/// The battle's logical clock. Real time is only ever turned into whole ticks,
+/// so outcomes don't depend on the frame rate.
+#[derive(Resource, Default)]
+pub struct BattleClock {
+    pub ticks: u64,
+    /// Real time not yet turned into a tick.
+    leftover: f32,
+}
+
+/// How the battle clock behaves while player units wait for orders.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AtbMode {
+    /// Time keeps flowing while the player is deciding.
+    #[default]
+    Active,
+    /// Time freezes while a player unit is ready or its orders are being chosen.
+    Wait,
+    /// Time jumps straight to the next player unit's turn.
+    TurnBased,
+}
+
+impl AtbMode {
+    pub fn next(self) -> Self {
+        match self {
+            AtbMode::Active => AtbMode::Wait,
+            AtbMode::Wait => AtbMode::TurnBased,
+            AtbMode::TurnBased => AtbMode::Active,
+        }
+    }
 }
 
-fn advance_unit_initiative(mut query: Query<(&mut Unit, &UnitStats)>, time: Res<Time>) {
-    for (mut unit, unit_stats) in &mut query {
+/// Whether a battle is underway, including while orders are being chosen.
+pub fn battle_in_progress(state: Res<State<TRPGState>>) -> bool {
+    matches!(
+        state.0,
+        TRPGState::Battle | TRPGState::ChoosingMove | TRPGState::ChoosingAttack
+    )
+}
+
+/// Only player units wait on orders; everyone else's initiative simply fills up.
+fn player_unit_ready(units: &Query<(&mut Unit, &UnitStats, &Faction)>) -> bool {
+    units.iter().any(|(unit, unit_stats, faction)| {
+        *faction == Faction::Player && unit.current_hp > 0 && unit.is_ready(unit_stats)
+    })
+}
+
+fn tick(clock: &mut BattleClock, units: &mut Query<(&mut Unit, &UnitStats, &Faction)>) {
+    clock.ticks += 1;
+    for (mut unit, unit_stats, _) in units.iter_mut() {
         unit.initiative =
-            (unit.initiative + time.delta_seconds()).clamp(0.0, unit_stats.max_initiative);
+            (unit.initiative + unit_stats.initiative_per_tick()).min(unit_stats.max_initiative);
+    }
+}
+
+fn advance_unit_initiative(
+    mut query: Query<(&mut Unit, &UnitStats, &Faction)>,
+    mut clock: ResMut<BattleClock>,
+    atb_mode: Res<AtbMode>,
+    state: Res<State<TRPGState>>,
+    time: Res<Time>,
+) {
+    let pauses_when_ready = *atb_mode != AtbMode::Active;
+    if pauses_when_ready && state.0 != TRPGState::Battle {
+        return;
+    }
+    let ticks = match *atb_mode {
+        AtbMode::TurnBased => MAX_JUMP_TICKS,
+        AtbMode::Active | AtbMode::Wait => {
+            clock.leftover += time.delta_seconds();
+            let ticks = (clock.leftover / TICK_SECONDS) as u32;
+            clock.leftover -= ticks as f32 * TICK_SECONDS;
+            ticks
+        }
+    };
+    for _ in 0..ticks {
+        if pauses_when_ready && player_unit_ready(&query) {
+            break;
+        }
+        tick(&mut clock, &mut query);
     }
 }
 
 #[derive(Clone, Copy)]
 pub enum UnitAction {
     Wait,
-    Attack { target: Entity },
+    /// Heavy attacks hit harder but cost more initiative.
+    Attack { target: Entity, heavy: bool },
 }
 
 #[derive(Clone, Copy)]
@@ -65,26 +218,185 @@ pub struct UnitTurn {
     pub action: UnitAction,
 }
 
-#[derive(Clone, Copy, Deref)]
-pub struct ValidatedTurn(UnitTurn);
+/// How much of the initiative bar each kind of action uses up, as a fraction
+/// of `max_initiative`. Whatever isn't used up carries over to the next turn.
+/// By default even the fastest units, at 6 tiles a turn, can move the whole
+/// way and attack heavily without running past the end of the bar.
+#[derive(Resource, Clone, Debug)]
+pub struct ActionCosts {
+    /// Waiting without moving.
+    pub wait_in_place: f32,
+    /// Waiting after moving.
+    pub wait: f32,
+    pub attack: f32,
+    pub heavy_attack: f32,
+    /// Added for every tile walked through on the way to the end position.
+    pub per_tile_moved: f32,
+}
+
+impl Default for ActionCosts {
+    fn default() -> Self {
+        Self {
+            wait_in_place: 0.4,
+            wait: 0.5,
+            attack: 0.55,
+            heavy_attack: 0.7,
+            per_tile_moved: 0.05,
+        }
+    }
+}
+
+impl ActionCosts {
+    /// The share of the initiative bar spent on walking `path`, which starts
+    /// on the unit's tile, and then taking `action`.
+    pub fn cost(&self, action: UnitAction, path: &[IVec2]) -> f32 {
+        let tiles_moved = path.len().saturating_sub(1) as f32;
+        let action_cost = match action {
+            UnitAction::Wait if tiles_moved == 0.0 => self.wait_in_place,
+            UnitAction::Wait => self.wait,
+            UnitAction::Attack { heavy: false, .. } => self.attack,
+            UnitAction::Attack { heavy: true, .. } => self.heavy_attack,
+        };
+        (action_cost + tiles_moved * self.per_tile_moved).clamp(0.0, 1.0)
+    }
+
+    /// The initiative a unit is left with after walking `path` and taking
+    /// `action`.
+    pub fn initiative_after(
+        &self,
+        action: UnitAction,
+        path: &[IVec2],
+        unit_stats: &UnitStats,
+    ) -> f32 {
+        unit_stats.max_initiative * (1.0 - self.cost(action, path))
+    }
+}
+
+#[derive(Clone)]
+pub struct ValidatedTurn {
+    turn: UnitTurn,
+    /// Every tile the unit walks through, from its start to its end position.
+    pub path: Vec<IVec2>,
+}
+
+impl std::ops::Deref for ValidatedTurn {
+    type Target = UnitTurn;
+
+    fn deref(&self) -> &UnitTurn {
+        &self.turn
+    }
+}
+
+/// Sent once a validated turn has moved its unit to the end position.
+#[derive(Clone)]
+pub struct UnitMoved {
+    pub unit: Entity,
+    pub path: Vec<IVec2>,
+}
+
+/// Sent for every tile a unit walks onto, in the order it walks them, so
+/// on-enter tile effects can react to each one.
+#[derive(Clone, Copy)]
+pub struct TileEntered {
+    pub unit: Entity,
+    pub position: IVec2,
+}
+
+/// Sent by the front-end once it has finished showing a `UnitMoved`. The
+/// unit's attack, if any, resolves only after this.
+#[derive(Clone, Copy)]
+pub struct MovementCompleted {
+    pub unit: Entity,
+}
+
+/// Why a `UnitTurn` failed validation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TurnRejection {
+    UnitMissing,
+    NotReady,
+    StartPositionMismatch,
+    DestinationOccupied,
+    DestinationUnreachable,
+    TargetMissing,
+    TargetDead,
+    TargetNotHostile,
+    TargetOutOfRange,
+    NoLineOfSight,
+}
+
+#[derive(Clone, Copy)]
+pub struct RejectedTurn {
+    pub turn: UnitTurn,
+    pub reason: TurnRejection,
+}
 
 #[derive(SystemParam)]
 struct ValidateMovementParam<'w, 's> {
     units: Query<'w, 's, &'static GridPosition>,
+    occupants: Query<'w, 's, (Entity, &'static GridPosition), With<Unit>>,
     reachable_tiles_param: reachable::ReachableTilesParam<'w, 's>,
 }
 
 impl<'w, 's> ValidateMovementParam<'w, 's> {
-    fn validate(&self, unit: Entity, start: IVec2, end: IVec2) -> bool {
-        let Ok(pos) = self.units.get(unit) else {return false};
+    /// Returns the path the unit takes to `end`.
+    fn validate(
+        &self,
+        unit: Entity,
+        start: IVec2,
+        end: IVec2,
+    ) -> Result<Vec<IVec2>, TurnRejection> {
+        let Ok(pos) = self.units.get(unit) else { return Err(TurnRejection::UnitMissing) };
         if pos.0 != start {
-            return false;
+            return Err(TurnRejection::StartPositionMismatch);
+        }
+        if self
+            .occupants
+            .iter()
+            .any(|(occupant, occupant_pos)| occupant != unit && occupant_pos.0 == end)
+        {
+            return Err(TurnRejection::DestinationOccupied);
+        }
+        let Some(reachable_map) = self.reachable_tiles_param.get_map(unit) else { return Err(TurnRejection::UnitMissing) };
+        if end.x < 0 || end.y < 0 {
+            return Err(TurnRejection::DestinationUnreachable);
+        }
+        let end = TilePos::new(end.x as u32, end.y as u32);
+        let Some(path) = reachable_map.contains(&end).then(|| reachable_map.path_to(&end)).flatten() else { return Err(TurnRejection::DestinationUnreachable) };
+        Ok(path
+            .into_iter()
+            .map(|tile| IVec2::new(tile.x as i32, tile.y as i32))
+            .collect())
+    }
+}
+
+#[derive(SystemParam)]
+struct ValidateAttackParam<'w, 's> {
+    attackers: Query<'w, 's, (&'static UnitRange, &'static Faction)>,
+    targets: Query<'w, 's, (&'static Unit, &'static GridPosition, &'static Faction)>,
+    relations: Res<'w, FactionRelations>,
+    line_of_sight_param: LineOfSightParam<'w, 's>,
+}
+
+impl<'w, 's> ValidateAttackParam<'w, 's> {
+    fn validate(&self, unit: Entity, from: IVec2, target: Entity) -> Result<(), TurnRejection> {
+        let Ok((unit_range, &faction)) = self.attackers.get(unit) else { return Err(TurnRejection::UnitMissing) };
+        let Ok((target_unit, target_pos, &target_faction)) = self.targets.get(target) else { return Err(TurnRejection::TargetMissing) };
+        if target_unit.current_hp == 0 {
+            return Err(TurnRejection::TargetDead);
         }
-        let Some(reachable_tiles) = self.reachable_tiles_param.get(unit) else {return false};
-        if !reachable_tiles.contains(&TilePos::new(end.x as u32, end.y as u32)) {
-            return false;
+        if !self.relations.is_hostile(faction, target_faction) {
+            return Err(TurnRejection::TargetNotHostile);
         }
-        return true;
+        if !in_range(&unit_range.valid_ranges, from, target_pos.0) {
+            return Err(TurnRejection::TargetOutOfRange);
+        }
+        if !self
+            .line_of_sight_param
+            .has_line_of_sight(from, target_pos.0)
+        {
+            return Err(TurnRejection::NoLineOfSight);
+        }
+        Ok(())
     }
 }
 
@@ -92,60 +404,162 @@ impl<'w, 's> ValidateMovementParam<'w, 's> {
 struct ValidateTurnParam<'w, 's> {
     units: Query<'w, 's, (&'static Unit, &'static UnitStats)>,
     validate_movement_param: ValidateMovementParam<'w, 's>,
+    validate_attack_param: ValidateAttackParam<'w, 's>,
 }
 
 impl<'w, 's> ValidateTurnParam<'w, 's> {
-    fn validate(&self, turn: &UnitTurn) -> Option<ValidatedTurn> {
-        let (unit, unit_stats) = self.units.get(turn.unit).ok()?;
-        if unit.initiative != unit_stats.max_initiative {
-            return None;
+    fn validate(&self, turn: &UnitTurn) -> Result<ValidatedTurn, TurnRejection> {
+        let Ok((unit, unit_stats)) = self.units.get(turn.unit) else { return Err(TurnRejection::UnitMissing) };
+        if !unit.is_ready(unit_stats) {
+            return Err(TurnRejection::NotReady);
         }
-        if !self
-            .validate_movement_param
-            .validate(turn.unit, turn.start_position, turn.end_position)
-        {
-            return None;
+        let path = self.validate_movement_param.validate(
+            turn.unit,
+            turn.start_position,
+            turn.end_position,
+        )?;
+        if let UnitAction::Attack { target, .. } = turn.action {
+            self.validate_attack_param
+                .validate(turn.unit, turn.end_position, target)?;
         }
-        Some(ValidatedTurn(*turn))
+        Ok(ValidatedTurn { turn: *turn, path })
     }
 }
 
 fn validate_turns(
     mut turns: EventReader<UnitTurn>,
     mut validated_turns: EventWriter<ValidatedTurn>,
+    mut rejected_turns: EventWriter<RejectedTurn>,
     validate_turn_param: ValidateTurnParam,
 ) {
-    validated_turns.send_batch(
-        turns
-            .iter()
-            .filter_map(|turn| validate_turn_param.validate(turn)),
-    );
+    for turn in turns.iter() {
+        match validate_turn_param.validate(turn) {
+            Ok(validated_turn) => validated_turns.send(validated_turn),
+            Err(reason) => rejected_turns.send(RejectedTurn {
+                turn: *turn,
+                reason,
+            }),
+        }
+    }
 }
 
+/// Moves units and spends their initiative. Units ending their turn on healing
+/// terrain recover some HP.
 fn apply_valid_turns(
-    mut units: Query<(&mut GridPosition, &mut Unit)>,
+    mut units: Query<(&mut GridPosition, &mut Unit, &UnitStats)>,
     mut turns: EventReader<ValidatedTurn>,
+    mut unit_moved: EventWriter<UnitMoved>,
+    mut tile_entered: EventWriter<TileEntered>,
+    action_costs: Res<ActionCosts>,
+    logic_tile_param: LogicTileParam,
 ) {
     for turn in turns.iter() {
-        let Ok((mut pos, mut unit)) = units.get_mut(turn.unit) else { continue };
+        let Ok((mut pos, mut unit, unit_stats)) = units.get_mut(turn.unit) else { continue };
         pos.0 = turn.end_position;
-        unit.initiative = 0.0;
+        unit.initiative = action_costs.initiative_after(turn.action, &turn.path, unit_stats);
+        unit.current_hp =
+            (unit.current_hp + logic_tile_param.heal_per_turn(pos.0)).min(unit_stats.max_hp);
+        tile_entered.send_batch(turn.path.iter().skip(1).map(|&position| TileEntered {
+            unit: turn.unit,
+            position,
+        }));
+        unit_moved.send(UnitMoved {
+            unit: turn.unit,
+            path: turn.path.clone(),
+        });
     }
 }
 
-fn apply_valid_attacks(
+/// Hurts units for every damaging tile they walk onto.
+fn apply_tile_entry_effects(
     mut units: Query<&mut Unit>,
-    unit_stats: Query<&UnitStats>,
+    mut tile_entered: EventReader<TileEntered>,
+    logic_tile_param: LogicTileParam,
+) {
+    for &TileEntered { unit, position } in tile_entered.iter() {
+        let Ok(mut unit) = units.get_mut(unit) else { continue };
+        unit.current_hp = unit
+            .current_hp
+            .saturating_sub(logic_tile_param.damage_on_enter(position));
+    }
+}
+
+/// Attacks waiting for their unit to finish moving.
+#[derive(Resource, Default)]
+struct PendingAttacks(Vec<UnitTurn>);
+
+fn queue_attacks(
+    mut pending_attacks: ResMut<PendingAttacks>,
     mut turns: EventReader<ValidatedTurn>,
 ) {
-    for turn in turns.iter() {
-        match turn.action {
-            UnitAction::Wait => {}
-            UnitAction::Attack { target } => {
-                let Ok(mut target_unit) = units.get_mut(target) else { continue };
-                let Ok(unit_stats) = unit_stats.get(turn.unit) else { continue };
-                target_unit.current_hp = target_unit.current_hp.saturating_sub(unit_stats.base_atk);
+    pending_attacks.0.extend(
+        turns
+            .iter()
+            .filter(|turn| matches!(turn.action, UnitAction::Attack { .. }))
+            .map(|turn| **turn),
+    );
+}
+
+/// Resolves queued attacks once the attacker has finished moving, followed by
+/// the defender's counterattack if it survives and has the attacker's tile in
+/// range.
+fn apply_valid_attacks(
+    mut units: Query<&mut Unit>,
+    unit_stats: Query<(&UnitStats, &UnitRange, &GridPosition)>,
+    logic_tile_param: LogicTileParam,
+    mut rng: ResMut<CombatRng>,
+    mut pending_attacks: ResMut<PendingAttacks>,
+    mut movement_completed: EventReader<MovementCompleted>,
+    mut damage_dealt: EventWriter<DamageDealt>,
+) {
+    for &MovementCompleted { unit } in movement_completed.iter() {
+        let (ready, waiting) = pending_attacks
+            .0
+            .drain(..)
+            .partition(|turn| turn.unit == unit);
+        pending_attacks.0 = waiting;
+        for turn in ready {
+            let UnitAction::Attack { target, heavy } = turn.action else { continue };
+            let Ok((attacker_stats, attacker_range, _)) = unit_stats.get(turn.unit) else { continue };
+            let Ok((target_stats, target_range, target_pos)) = unit_stats.get(target) else { continue };
+            let Ok([mut attacker_unit, mut target_unit]) = units.get_many_mut([turn.unit, target]) else { continue };
+            let attacker = Combatant {
+                stats: attacker_stats,
+                hp: attacker_unit.current_hp,
+                position: turn.end_position,
+                ranges: &attacker_range.valid_ranges,
+                terrain: logic_tile_param.terrain_modifiers(turn.end_position),
+            };
+            let defender = Combatant {
+                stats: target_stats,
+                hp: target_unit.current_hp,
+                position: target_pos.0,
+                ranges: &target_range.valid_ranges,
+                terrain: logic_tile_param.terrain_modifiers(target_pos.0),
+            };
+            let breakdown = AttackProfile {
+                heavy,
+                ..AttackProfile::new(&attacker, &defender)
+            }
+            .roll(&mut rng);
+            target_unit.current_hp = target_unit.current_hp.saturating_sub(breakdown.damage);
+            damage_dealt.send(DamageDealt {
+                attacker: turn.unit,
+                target,
+                breakdown,
+                counter: false,
+            });
+            if target_unit.current_hp == 0 || !can_counter(&defender, attacker.position) {
+                continue;
             }
+            let breakdown = AttackProfile::new(&defender, &attacker).roll(&mut rng);
+            attacker_unit.current_hp = attacker_unit.current_hp.saturating_sub(breakdown.damage);
+            damage_dealt.send(DamageDealt {
+                attacker: target,
+                target: turn.unit,
+                breakdown,
+                counter: true,
+            });
         }
     }
 }
@@ -155,21 +569,474 @@ pub struct LogicPlugin;
 impl Plugin for LogicPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(TilePlugin)
+            .add_plugin(TerrainPlugin)
+            .add_plugin(FactionPlugin)
+            .add_system(advance_unit_initiative.run_if(battle_in_progress))
             .add_systems(
                 (
-                    advance_unit_initiative,
                     validate_turns,
                     apply_valid_turns,
-                    apply_valid_attacks,
+                    apply_tile_entry_effects,
+                    expire_status_effects,
+                    queue_attacks,
                 )
+                    .chain()
                     .in_set(OnUpdate(TRPGState::Battle)),
             )
+            // Movement can finish after the next unit's orders are already
+            // being chosen, so attacks resolve in any battle state.
+            .add_systems(
+                (apply_valid_attacks, kill_units)
+                    .chain()
+                    .after(queue_attacks)
+                    .distributive_run_if(battle_in_progress),
+            )
+            .init_resource::<BattleClock>()
+            .init_resource::<AtbMode>()
+            .init_resource::<ActionCosts>()
+            .init_resource::<CombatRng>()
+            .init_resource::<PendingAttacks>()
             .add_event::<UnitTurn>()
             .add_event::<ValidatedTurn>()
+            .add_event::<RejectedTurn>()
+            .add_event::<UnitMoved>()
+            .add_event::<TileEntered>()
+            .add_event::<MovementCompleted>()
+            .add_event::<DamageDealt>()
+            .add_event::<UnitDied>()
             .register_type::<GridPosition>()
             .register_type::<Unit>()
             .register_type::<UnitStats>()
             .register_type::<UnitSpeed>()
-            .register_type::<UnitRange>();
+            .register_type::<MovementType>()
+            .register_type::<UnitRange>()
+            .register_type::<DeathDrop>()
+            .register_type::<Remains>()
+            .register_type::<StatusEffects>()
+            .register_type::<Equipment>();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{ecs::event::Events, prelude::*};
+
+    use super::*;
+
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<FactionRelations>()
+            .add_event::<UnitTurn>()
+            .add_event::<ValidatedTurn>()
+            .add_event::<RejectedTurn>()
+            .add_system(validate_turns);
+        app
+    }
+
+    fn spawn_unit(app: &mut App, position: IVec2, initiative: f32) -> Entity {
+        app.world
+            .spawn(UnitLogicBundle {
+                unit: Unit {
+                    initiative,
+                    current_hp: 5,
+                },
+                unit_stats: UnitStats {
+                    max_hp: 5,
+                    max_initiative: 5.0,
+                    base_atk: 3,
+                    base_armor: 2,
+                    agility: BASE_AGILITY,
+                },
+                unit_range: UnitRange {
+                    valid_ranges: vec![1],
+                },
+                unit_speed: UnitSpeed(5),
+                movement_type: MovementType::Foot,
+                grid_position: GridPosition(position),
+                faction: Faction::Player,
+            })
+            .id()
+    }
+
+    fn rejections(app: &mut App, turn: UnitTurn) -> Vec<TurnRejection> {
+        app.world.resource_mut::<Events<UnitTurn>>().send(turn);
+        app.update();
+        let events = app.world.resource::<Events<RejectedTurn>>();
+        events
+            .get_reader()
+            .iter(events)
+            .map(|rejected_turn| rejected_turn.reason)
+            .collect()
+    }
+
+    fn wait_turn(unit: Entity, start_position: IVec2, end_position: IVec2) -> UnitTurn {
+        UnitTurn {
+            unit,
+            start_position,
+            end_position,
+            action: UnitAction::Wait,
+        }
+    }
+
+    fn attack_turn(unit: Entity, position: IVec2, target: Entity) -> UnitTurn {
+        UnitTurn {
+            unit,
+            start_position: position,
+            end_position: position,
+            action: UnitAction::Attack {
+                target,
+                heavy: false,
+            },
+        }
+    }
+
+    /// An attacker at (0, 0) and an enemy `distance` tiles east of it on a
+    /// plain strip with sight blocked on `walls`.
+    fn attack_app(distance: i32, walls: &[IVec2]) -> (App, Entity, Entity) {
+        let mut app = test_app();
+        spawn_test_tiles(&mut app.world, UVec2::new(5, 1), walls);
+        let attacker = spawn_unit(&mut app, IVec2::new(0, 0), 5.0);
+        let target = spawn_unit(&mut app, IVec2::new(distance, 0), 0.0);
+        *app.world.get_mut::<Faction>(target).unwrap() = Faction::Enemy;
+        (app, attacker, target)
+    }
+
+    #[test]
+    fn missing_unit_is_rejected() {
+        let mut app = test_app();
+        let unit = spawn_unit(&mut app, IVec2::new(1, 1), 5.0);
+        app.world.despawn(unit);
+        assert_eq!(
+            rejections(&mut app, wait_turn(unit, IVec2::new(1, 1), IVec2::new(1, 1))),
+            vec![TurnRejection::UnitMissing]
+        );
+    }
+
+    #[test]
+    fn unit_without_full_initiative_is_rejected() {
+        let mut app = test_app();
+        let unit = spawn_unit(&mut app, IVec2::new(1, 1), 2.5);
+        assert_eq!(
+            rejections(&mut app, wait_turn(unit, IVec2::new(1, 1), IVec2::new(1, 1))),
+            vec![TurnRejection::NotReady]
+        );
+    }
+
+    #[test]
+    fn wrong_start_position_is_rejected() {
+        let mut app = test_app();
+        let unit = spawn_unit(&mut app, IVec2::new(1, 1), 5.0);
+        assert_eq!(
+            rejections(&mut app, wait_turn(unit, IVec2::new(2, 1), IVec2::new(2, 1))),
+            vec![TurnRejection::StartPositionMismatch]
+        );
+    }
+
+    #[test]
+    fn destination_off_the_map_is_rejected() {
+        let mut app = test_app();
+        let unit = spawn_unit(&mut app, IVec2::new(1, 1), 5.0);
+        assert_eq!(
+            rejections(&mut app, wait_turn(unit, IVec2::new(1, 1), IVec2::new(-1, 1))),
+            vec![TurnRejection::DestinationUnreachable]
+        );
+    }
+
+    #[test]
+    fn destination_with_another_unit_is_rejected() {
+        let mut app = test_app();
+        let unit = spawn_unit(&mut app, IVec2::new(1, 1), 5.0);
+        spawn_unit(&mut app, IVec2::new(2, 1), 0.0);
+        assert_eq!(
+            rejections(
+                &mut app,
+                wait_turn(unit, IVec2::new(1, 1), IVec2::new(2, 1))
+            ),
+            vec![TurnRejection::DestinationOccupied]
+        );
+    }
+
+    #[test]
+    fn attack_in_range_is_accepted() {
+        let (mut app, attacker, target) = attack_app(1, &[]);
+        assert_eq!(
+            rejections(&mut app, attack_turn(attacker, IVec2::new(0, 0), target)),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn missing_target_is_rejected() {
+        let (mut app, attacker, target) = attack_app(1, &[]);
+        app.world.despawn(target);
+        assert_eq!(
+            rejections(&mut app, attack_turn(attacker, IVec2::new(0, 0), target)),
+            vec![TurnRejection::TargetMissing]
+        );
+    }
+
+    #[test]
+    fn dead_target_is_rejected() {
+        let (mut app, attacker, target) = attack_app(1, &[]);
+        app.world.get_mut::<Unit>(target).unwrap().current_hp = 0;
+        assert_eq!(
+            rejections(&mut app, attack_turn(attacker, IVec2::new(0, 0), target)),
+            vec![TurnRejection::TargetDead]
+        );
+    }
+
+    #[test]
+    fn non_hostile_target_is_rejected() {
+        let (mut app, attacker, target) = attack_app(1, &[]);
+        *app.world.get_mut::<Faction>(target).unwrap() = Faction::Player;
+        assert_eq!(
+            rejections(&mut app, attack_turn(attacker, IVec2::new(0, 0), target)),
+            vec![TurnRejection::TargetNotHostile]
+        );
+    }
+
+    #[test]
+    fn target_out_of_range_is_rejected() {
+        let (mut app, attacker, target) = attack_app(3, &[]);
+        assert_eq!(
+            rejections(&mut app, attack_turn(attacker, IVec2::new(0, 0), target)),
+            vec![TurnRejection::TargetOutOfRange]
+        );
+    }
+
+    #[test]
+    fn target_behind_a_wall_is_rejected() {
+        let (mut app, attacker, target) = attack_app(3, &[IVec2::new(1, 0)]);
+        app.world
+            .get_mut::<UnitRange>(attacker)
+            .unwrap()
+            .valid_ranges = vec![3];
+        assert_eq!(
+            rejections(&mut app, attack_turn(attacker, IVec2::new(0, 0), target)),
+            vec![TurnRejection::NoLineOfSight]
+        );
+    }
+
+    #[test]
+    fn every_tile_walked_onto_is_entered() {
+        let mut app = test_app();
+        app.init_resource::<ActionCosts>()
+            .add_event::<UnitMoved>()
+            .add_event::<TileEntered>()
+            .add_system(apply_valid_turns.after(validate_turns))
+            .add_system(apply_tile_entry_effects.after(apply_valid_turns));
+        spawn_test_tiles(&mut app.world, UVec2::new(4, 1), &[]);
+        for mut logic_tile in app.world.query::<&mut LogicTile>().iter_mut(&mut app.world) {
+            logic_tile.damage_on_enter = 1;
+        }
+        let unit = spawn_unit(&mut app, IVec2::new(0, 0), 5.0);
+        assert_eq!(
+            rejections(&mut app, wait_turn(unit, IVec2::new(0, 0), IVec2::new(3, 0))),
+            vec![]
+        );
+        let events = app.world.resource::<Events<TileEntered>>();
+        let entered: Vec<_> = events
+            .get_reader()
+            .iter(events)
+            .map(|tile_entered| tile_entered.position)
+            .collect();
+        assert_eq!(
+            entered,
+            vec![IVec2::new(1, 0), IVec2::new(2, 0), IVec2::new(3, 0)]
+        );
+        assert_eq!(app.world.get::<Unit>(unit).unwrap().current_hp, 2);
+    }
+
+    #[test]
+    fn attacks_wait_for_the_attacker_to_finish_moving() {
+        let (mut app, attacker, target) = attack_app(2, &[]);
+        app.insert_resource(CombatRng::seeded(0))
+            .init_resource::<ActionCosts>()
+            .init_resource::<PendingAttacks>()
+            .add_event::<UnitMoved>()
+            .add_event::<TileEntered>()
+            .add_event::<MovementCompleted>()
+            .add_event::<DamageDealt>()
+            .add_systems(
+                (apply_valid_turns, queue_attacks, apply_valid_attacks)
+                    .chain()
+                    .after(validate_turns),
+            );
+        let turn = UnitTurn {
+            end_position: IVec2::new(1, 0),
+            ..attack_turn(attacker, IVec2::new(0, 0), target)
+        };
+        let damage_dealt = |app: &App| app.world.resource::<Events<DamageDealt>>().len();
+        assert_eq!(rejections(&mut app, turn), vec![]);
+        assert_eq!(app.world.resource::<PendingAttacks>().0.len(), 1);
+        assert_eq!(damage_dealt(&app), 0);
+
+        app.world
+            .resource_mut::<Events<MovementCompleted>>()
+            .send(MovementCompleted { unit: target });
+        app.update();
+        assert_eq!(app.world.resource::<PendingAttacks>().0.len(), 1);
+        assert_eq!(damage_dealt(&app), 0);
+
+        app.world
+            .resource_mut::<Events<MovementCompleted>>()
+            .send(MovementCompleted { unit: attacker });
+        app.update();
+        assert!(app.world.resource::<PendingAttacks>().0.is_empty());
+        assert!(damage_dealt(&app) > 0);
+    }
+
+    /// Resolves `attacker`'s attack on `target` from where it stands and
+    /// returns which of the resulting strikes were counters.
+    fn resolve_attack(app: &mut App, attacker: Entity, target: Entity) -> Vec<bool> {
+        app.insert_resource(CombatRng::seeded(0))
+            .init_resource::<PendingAttacks>()
+            .add_event::<MovementCompleted>()
+            .add_event::<DamageDealt>()
+            .add_system(apply_valid_attacks);
+        let position = app.world.get::<GridPosition>(attacker).unwrap().0;
+        app.world
+            .resource_mut::<PendingAttacks>()
+            .0
+            .push(attack_turn(attacker, position, target));
+        app.world
+            .resource_mut::<Events<MovementCompleted>>()
+            .send(MovementCompleted { unit: attacker });
+        app.update();
+        let events = app.world.resource::<Events<DamageDealt>>();
+        events
+            .get_reader()
+            .iter(events)
+            .map(|damage_dealt| damage_dealt.counter)
+            .collect()
+    }
+
+    #[test]
+    fn defender_in_range_counters() {
+        let (mut app, attacker, target) = attack_app(1, &[]);
+        assert_eq!(
+            resolve_attack(&mut app, attacker, target),
+            vec![false, true]
+        );
+    }
+
+    #[test]
+    fn killed_defender_does_not_counter() {
+        let (mut app, attacker, target) = attack_app(1, &[]);
+        app.world.get_mut::<UnitStats>(attacker).unwrap().agility = BASE_AGILITY + 10;
+        app.world.get_mut::<Unit>(target).unwrap().current_hp = 1;
+        assert_eq!(resolve_attack(&mut app, attacker, target), vec![false]);
+        assert_eq!(app.world.get::<Unit>(target).unwrap().current_hp, 0);
+    }
+
+    #[test]
+    fn defender_out_of_range_does_not_counter() {
+        let (mut app, attacker, target) = attack_app(2, &[]);
+        app.world
+            .get_mut::<UnitRange>(attacker)
+            .unwrap()
+            .valid_ranges = vec![2];
+        assert_eq!(resolve_attack(&mut app, attacker, target), vec![false]);
+    }
+
+    #[test]
+    fn turn_based_mode_jumps_to_the_next_ready_unit() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<BattleClock>()
+            .insert_resource(AtbMode::TurnBased)
+            .insert_resource(State(TRPGState::Battle))
+            .add_system(advance_unit_initiative);
+        let slow = spawn_unit(&mut app, IVec2::new(1, 1), 1.0);
+        let fast = spawn_unit(&mut app, IVec2::new(2, 1), 3.0);
+        app.update();
+        let unit_stats = app.world.get::<UnitStats>(fast).unwrap().clone();
+        assert!(app.world.get::<Unit>(fast).unwrap().is_ready(&unit_stats));
+        let slow_initiative = app.world.get::<Unit>(slow).unwrap().initiative;
+        assert!(slow_initiative > 2.9 && slow_initiative < 3.1);
+        assert!(app.world.resource::<BattleClock>().ticks >= 20);
+        app.update();
+        assert_eq!(
+            app.world.get::<Unit>(slow).unwrap().initiative,
+            slow_initiative
+        );
+    }
+
+    #[test]
+    fn ties_go_to_the_more_agile_unit() {
+        let mut app = App::new();
+        let nimble = spawn_unit(&mut app, IVec2::new(1, 1), 5.0);
+        let sluggish = spawn_unit(&mut app, IVec2::new(2, 1), 5.0);
+        app.world.get_mut::<UnitStats>(nimble).unwrap().agility = BASE_AGILITY + 2;
+        let key = |entity| {
+            turn_order_key(
+                entity,
+                app.world.get::<Unit>(entity).unwrap(),
+                app.world.get::<UnitStats>(entity).unwrap(),
+            )
+        };
+        assert!(key(nimble) < key(sluggish));
+    }
+
+    #[test]
+    fn actions_cost_different_amounts_of_initiative() {
+        let action_costs = ActionCosts::default();
+        let target = Entity::from_raw(0);
+        let in_place = [IVec2::new(1, 1)];
+        let straight = [IVec2::new(1, 1), IVec2::new(2, 1), IVec2::new(3, 1)];
+        let attack = UnitAction::Attack {
+            target,
+            heavy: false,
+        };
+        let heavy_attack = UnitAction::Attack {
+            target,
+            heavy: true,
+        };
+        assert!(
+            action_costs.cost(UnitAction::Wait, &in_place)
+                < action_costs.cost(UnitAction::Wait, &straight)
+        );
+        assert!(
+            action_costs.cost(UnitAction::Wait, &straight) < action_costs.cost(attack, &straight)
+        );
+        assert!(action_costs.cost(attack, &in_place) < action_costs.cost(heavy_attack, &in_place));
+        assert!(action_costs.cost(heavy_attack, &straight) <= 1.0);
+    }
+
+    #[test]
+    fn every_tile_moved_costs_initiative() {
+        let action_costs = ActionCosts::default();
+        let path = |tiles: i32| (0..=tiles).map(|x| IVec2::new(x, 0)).collect::<Vec<_>>();
+        let heavy_attack = UnitAction::Attack {
+            target: Entity::from_raw(0),
+            heavy: true,
+        };
+        assert!(
+            action_costs.cost(heavy_attack, &path(3)) > action_costs.cost(heavy_attack, &path(0))
+        );
+        assert!(
+            action_costs.cost(heavy_attack, &path(6)) > action_costs.cost(heavy_attack, &path(4))
+        );
+        assert!(
+            action_costs.cost(UnitAction::Wait, &path(6))
+                > action_costs.cost(UnitAction::Wait, &path(4))
+        );
+    }
+
+    #[test]
+    fn detours_cost_more_than_straight_moves() {
+        let action_costs = ActionCosts::default();
+        let straight = [IVec2::new(1, 1), IVec2::new(2, 1), IVec2::new(3, 1)];
+        let detour = [
+            IVec2::new(1, 1),
+            IVec2::new(1, 2),
+            IVec2::new(2, 2),
+            IVec2::new(3, 2),
+            IVec2::new(3, 1),
+        ];
+        assert!(
+            action_costs.cost(UnitAction::Wait, &straight)
+                < action_costs.cost(UnitAction::Wait, &detour)
+        );
     }
 }
@@ -0,0 +1,21 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// How a unit gets around, which decides what each terrain costs it.
+#[derive(Component, Reflect, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MovementType {
+    #[default]
+    Foot,
+    Mounted,
+    Flying,
+    Aquatic,
+}
+
+impl MovementType {
+    pub const ALL: [MovementType; 4] = [
+        MovementType::Foot,
+        MovementType::Mounted,
+        MovementType::Flying,
+        MovementType::Aquatic,
+    ];
+}
@@ -6,64 +6,182 @@ use bevy_ecs_tilemap::helpers::square_grid::neighbors::Neighbors;
 use std::cmp::Reverse;
 use std::collections::BinaryHeap;
 
+use bevy_ecs_tilemap::map::TilemapSize;
 use bevy_ecs_tilemap::tiles::TilePos;
 
 use super::GetTileStorageParam;
 use super::UnitSpeed;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use super::GridPosition;
+use super::{Faction, FactionRelations, GridPosition, MovementType, Unit};
 
 use super::LogicTile;
 
+/// The result of a walk from `start`: what each explored tile costs to enter
+/// and which tile it is entered from.
+#[derive(Clone, Debug)]
+pub struct ReachableMap {
+    start: TilePos,
+    costs: HashMap<TilePos, u32>,
+    predecessors: HashMap<TilePos, TilePos>,
+    /// Explored tiles the unit may also end its move on.
+    tiles: HashSet<TilePos>,
+}
+
+impl ReachableMap {
+    fn new(start: TilePos) -> Self {
+        Self {
+            start,
+            costs: HashMap::from([(start, 0)]),
+            predecessors: HashMap::new(),
+            tiles: HashSet::new(),
+        }
+    }
+
+    pub fn into_tiles(self) -> HashSet<TilePos> {
+        self.tiles
+    }
+
+    pub fn contains(&self, tile: &TilePos) -> bool {
+        self.tiles.contains(tile)
+    }
+
+    /// Every tile from the start up to and including `tile`.
+    pub fn path_to(&self, tile: &TilePos) -> Option<Vec<TilePos>> {
+        self.costs.get(tile)?;
+        let mut path = vec![*tile];
+        let mut current = *tile;
+        while current != self.start {
+            current = *self.predecessors.get(&current)?;
+            path.push(current);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
 #[derive(SystemParam)]
 pub struct TileWalkerParam<'w, 's> {
     logical_tiles: Query<'w, 's, &'static LogicTile>,
     tile_storage: GetTileStorageParam<'w, 's>,
+    units: Query<'w, 's, (&'static GridPosition, &'static Faction), With<Unit>>,
+    relations: Res<'w, FactionRelations>,
 }
 
 impl<'w, 's> TileWalkerParam<'w, 's> {
-    fn get_reachable_tiles(&self, pos: IVec2, speed: u32) -> HashSet<TilePos> {
-        let mut reachable_tiles = HashSet::new();
-        let Some(tile_storage) = self.tile_storage.get() else { return reachable_tiles; };
+    /// Tiles taken by units that `include` accepts.
+    fn get_unit_tiles(&self, include: impl Fn(Faction) -> bool) -> HashSet<TilePos> {
+        self.units
+            .iter()
+            .filter(|(_, other_faction)| include(**other_faction))
+            .filter(|(pos, _)| pos.x >= 0 && pos.y >= 0)
+            .map(|(pos, _)| TilePos::new(pos.x as u32, pos.y as u32))
+            .collect()
+    }
+
+    /// Hostile and neutral units can't be walked through, while allied units
+    /// can be passed but not stopped on.
+    fn get_reachable_tiles(
+        &self,
+        pos: IVec2,
+        speed: u32,
+        movement_type: MovementType,
+        faction: Faction,
+    ) -> ReachableMap {
         let starting_pos = TilePos {
             x: pos.x as u32,
             y: pos.y as u32,
         };
-        let mut tiles_to_explore = BinaryHeap::new();
-        tiles_to_explore.push(TileExploreQueueItem::new(0, starting_pos));
-        reachable_tiles.insert(starting_pos);
+        let Some(tile_storage) = self.tile_storage.get() else { return ReachableMap::new(starting_pos); };
+        let blocked_tiles =
+            self.get_unit_tiles(|other_faction| !self.relations.is_allied(faction, other_faction));
+        let occupied_tiles = self.get_unit_tiles(|_| true);
+        let mut reachable_map = walk_tiles(starting_pos, &tile_storage.size, speed, |tile_pos| {
+            if blocked_tiles.contains(tile_pos) {
+                return None;
+            }
+            let tile = tile_storage
+                .get(tile_pos)
+                .and_then(|e| self.logical_tiles.get(e).ok())?;
+            tile.move_costs.get(&movement_type).copied()
+        });
+        reachable_map.tiles = reachable_map
+            .costs
+            .keys()
+            .filter(|tile| **tile == starting_pos || !occupied_tiles.contains(*tile))
+            .copied()
+            .collect();
+        reachable_map
+    }
+}
 
-        while let Some(TileExploreQueueItem { cost, pos }) = tiles_to_explore.pop() {
-            let neighbors =
-                Neighbors::get_square_neighboring_positions(&pos, &tile_storage.size, false);
+/// Finds the cheapest route from `start` to every tile within `speed`.
+/// `move_cost` is what entering a tile costs, or `None` if it can't be entered.
+fn walk_tiles(
+    start: TilePos,
+    size: &TilemapSize,
+    speed: u32,
+    move_cost: impl Fn(&TilePos) -> Option<u32>,
+) -> ReachableMap {
+    let mut reachable_map = ReachableMap::new(start);
+    let mut tiles_to_explore = BinaryHeap::new();
+    tiles_to_explore.push(TileExploreQueueItem::new(0, start));
 
-            for neighbor_pos in neighbors.iter() {
-                if reachable_tiles.contains(neighbor_pos) {
-                    continue;
-                }
-                let Some(neighbor_tile) = tile_storage.get(neighbor_pos).and_then(|e| self.logical_tiles.get(e).ok()) else { continue };
-                let neighbor_cost = cost + neighbor_tile.move_cost;
-                if neighbor_cost <= speed && neighbor_tile.can_move {
-                    tiles_to_explore.push(TileExploreQueueItem::new(neighbor_cost, *neighbor_pos));
-                    reachable_tiles.insert(*neighbor_pos);
-                }
+    while let Some(TileExploreQueueItem { cost, pos }) = tiles_to_explore.pop() {
+        // A cheaper route to this tile was already explored.
+        if reachable_map
+            .costs
+            .get(&pos)
+            .is_some_and(|&best| best < cost)
+        {
+            continue;
+        }
+        let neighbors = Neighbors::get_square_neighboring_positions(&pos, size, false);
+        for neighbor_pos in neighbors.iter() {
+            let Some(neighbor_cost) = move_cost(neighbor_pos).map(|move_cost| cost + move_cost) else { continue };
+            let improves = reachable_map
+                .costs
+                .get(neighbor_pos)
+                .is_none_or(|&best| neighbor_cost < best);
+            if neighbor_cost <= speed && improves {
+                tiles_to_explore.push(TileExploreQueueItem::new(neighbor_cost, *neighbor_pos));
+                reachable_map.costs.insert(*neighbor_pos, neighbor_cost);
+                reachable_map.predecessors.insert(*neighbor_pos, pos);
             }
         }
-        reachable_tiles
     }
+    reachable_map
 }
 
 #[derive(SystemParam)]
 pub struct ReachableTilesParam<'w, 's> {
     tile_walker_param: TileWalkerParam<'w, 's>,
-    units: Query<'w, 's, (&'static GridPosition, &'static UnitSpeed)>,
+    units: Query<
+        'w,
+        's,
+        (
+            &'static GridPosition,
+            &'static UnitSpeed,
+            &'static MovementType,
+            &'static Faction,
+        ),
+    >,
 }
 
 impl<'w, 's> ReachableTilesParam<'w, 's> {
+    /// Tiles `unit` may end its move on.
     pub fn get(&self, unit: Entity) -> Option<HashSet<TilePos>> {
-        let (&GridPosition(pos), &UnitSpeed(speed)) = self.units.get(unit).ok()?;
-        Some(self.tile_walker_param.get_reachable_tiles(pos, speed))
+        self.get_map(unit).map(ReachableMap::into_tiles)
+    }
+
+    /// Costs and paths to every tile `unit` can walk to.
+    pub fn get_map(&self, unit: Entity) -> Option<ReachableMap> {
+        let (&GridPosition(pos), &UnitSpeed(speed), &movement_type, &faction) =
+            self.units.get(unit).ok()?;
+        Some(
+            self.tile_walker_param
+                .get_reachable_tiles(pos, speed, movement_type, faction),
+        )
     }
 }
 
@@ -143,11 +261,16 @@ fn get_range_offsets(range: u32) -> impl Iterator<Item = IVec2> {
 
 #[cfg(test)]
 mod test {
-    use std::collections::HashSet;
+    use std::collections::{HashMap, HashSet};
+
+    use bevy::{ecs::system::SystemState, prelude::*};
+    use bevy_ecs_tilemap::{map::TilemapSize, tiles::TilePos};
 
-    use bevy::prelude::IVec2;
+    use crate::logic::{
+        spawn_test_tiles, Faction, FactionRelations, GridPosition, MovementType, Unit,
+    };
 
-    use super::get_range_offsets;
+    use super::{get_range_offsets, walk_tiles, TileWalkerParam};
 
     fn test_range_offsets(range: u32) {
         let range_offsets: HashSet<_> = get_range_offsets(range).collect();
@@ -170,4 +293,115 @@ mod test {
             test_range_offsets(r);
         }
     }
+
+    /// A random grid where each tile costs 1 to 3 to enter, or is a wall.
+    fn random_grid(rng: &mut fastrand::Rng) -> (TilemapSize, HashMap<TilePos, Option<u32>>) {
+        let size = TilemapSize {
+            x: rng.u32(1..8),
+            y: rng.u32(1..8),
+        };
+        let mut grid = HashMap::new();
+        for x in 0..size.x {
+            for y in 0..size.y {
+                let move_cost = (rng.f32() > 0.2).then(|| rng.u32(1..=3));
+                grid.insert(TilePos::new(x, y), move_cost);
+            }
+        }
+        (size, grid)
+    }
+
+    /// Relaxes every step between neighbors until no cost improves.
+    fn brute_force_costs(
+        start: TilePos,
+        grid: &HashMap<TilePos, Option<u32>>,
+    ) -> HashMap<TilePos, u32> {
+        let mut costs = HashMap::from([(start, 0)]);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (&pos, &move_cost) in grid.iter() {
+                let Some(move_cost) = move_cost else { continue };
+                let best_neighbor = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                    .into_iter()
+                    .filter_map(|(dx, dy)| {
+                        let neighbor = TilePos::new(
+                            pos.x.checked_add_signed(dx)?,
+                            pos.y.checked_add_signed(dy)?,
+                        );
+                        costs.get(&neighbor).copied()
+                    })
+                    .min();
+                let Some(cost) = best_neighbor.map(|cost| cost + move_cost) else { continue };
+                if pos != start && costs.get(&pos).is_none_or(|&best| cost < best) {
+                    costs.insert(pos, cost);
+                    changed = true;
+                }
+            }
+        }
+        costs
+    }
+
+    #[test]
+    fn walk_tiles_matches_brute_force() {
+        let mut rng = fastrand::Rng::with_seed(20);
+        for _ in 0..500 {
+            let (size, grid) = random_grid(&mut rng);
+            let start = TilePos::new(rng.u32(0..size.x), rng.u32(0..size.y));
+            let speed = rng.u32(0..12);
+            let reachable_map = walk_tiles(start, &size, speed, |pos| grid[pos]);
+
+            let expected: HashMap<_, _> = brute_force_costs(start, &grid)
+                .into_iter()
+                .filter(|(_, cost)| *cost <= speed)
+                .collect();
+            assert_eq!(reachable_map.costs, expected);
+
+            for (tile, cost) in expected {
+                let path = reachable_map
+                    .path_to(&tile)
+                    .expect("reached tiles have a path");
+                assert_eq!(path.first(), Some(&start));
+                assert_eq!(path.last(), Some(&tile));
+                let path_cost: u32 = path[1..].iter().map(|pos| grid[pos].unwrap()).sum();
+                assert_eq!(path_cost, cost);
+            }
+        }
+    }
+
+    #[test]
+    fn allies_are_passed_and_hostiles_block() {
+        let mut world = World::new();
+        world.init_resource::<FactionRelations>();
+        spawn_test_tiles(&mut world, UVec2::new(7, 1), &[]);
+        for (x, faction) in [(2, Faction::Player), (4, Faction::Enemy)] {
+            world.spawn((
+                Unit {
+                    initiative: 0.0,
+                    current_hp: 5,
+                },
+                GridPosition(IVec2::new(x, 0)),
+                faction,
+            ));
+        }
+
+        let mut state: SystemState<TileWalkerParam> = SystemState::new(&mut world);
+        let reachable_map = state.get(&world).get_reachable_tiles(
+            IVec2::ZERO,
+            6,
+            MovementType::Foot,
+            Faction::Player,
+        );
+        assert!(reachable_map.contains(&TilePos::new(1, 0)));
+        assert!(!reachable_map.contains(&TilePos::new(2, 0)));
+        assert!(reachable_map.contains(&TilePos::new(3, 0)));
+        assert_eq!(
+            reachable_map
+                .path_to(&TilePos::new(3, 0))
+                .map(|path| path.len()),
+            Some(4)
+        );
+        for x in 4..7 {
+            assert!(reachable_map.path_to(&TilePos::new(x, 0)).is_none());
+        }
+    }
 }
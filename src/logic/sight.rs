@@ -0,0 +1,69 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use super::LogicTileParam;
+
+/// The tiles strictly between `start` and `end` on a Bresenham line.
+fn tiles_between(start: IVec2, end: IVec2) -> Vec<IVec2> {
+    let delta = (end - start).abs();
+    let step = (end - start).signum();
+    let mut error = delta.x - delta.y;
+    let mut pos = start;
+    let mut tiles = Vec::new();
+    while pos != end {
+        let doubled_error = 2 * error;
+        if doubled_error > -delta.y {
+            error -= delta.y;
+            pos.x += step.x;
+        }
+        if doubled_error < delta.x {
+            error += delta.x;
+            pos.y += step.y;
+        }
+        if pos != end {
+            tiles.push(pos);
+        }
+    }
+    tiles
+}
+
+#[derive(SystemParam)]
+pub struct LineOfSightParam<'w, 's> {
+    logic_tile_param: LogicTileParam<'w, 's>,
+}
+
+impl<'w, 's> LineOfSightParam<'w, 's> {
+    pub fn has_line_of_sight(&self, start: IVec2, end: IVec2) -> bool {
+        tiles_between(start, end)
+            .into_iter()
+            .all(|pos| !self.logic_tile_param.blocks_sight(pos))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::tiles_between;
+
+    #[test]
+    fn tiles_between_excludes_endpoints() {
+        assert_eq!(
+            tiles_between(IVec2::new(0, 0), IVec2::new(3, 0)),
+            vec![IVec2::new(1, 0), IVec2::new(2, 0)]
+        );
+        assert_eq!(tiles_between(IVec2::new(2, 2), IVec2::new(2, 3)), vec![]);
+        assert_eq!(tiles_between(IVec2::new(2, 2), IVec2::new(2, 2)), vec![]);
+    }
+
+    #[test]
+    fn tiles_between_follows_diagonals() {
+        assert_eq!(
+            tiles_between(IVec2::new(0, 0), IVec2::new(-3, -3)),
+            vec![IVec2::new(-1, -1), IVec2::new(-2, -2)]
+        );
+        assert_eq!(
+            tiles_between(IVec2::new(0, 0), IVec2::new(2, 4)),
+            vec![IVec2::new(0, 1), IVec2::new(1, 2), IVec2::new(1, 3)]
+        );
+    }
+}
@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+
+use super::ValidatedTurn;
+
+/// A lasting effect on a unit, such as a poison or a blessing.
+#[derive(Reflect, FromReflect, Clone, Debug, PartialEq)]
+pub struct StatusEffect {
+    pub name: String,
+    /// Turns the unit still has to take before the effect wears off.
+    pub turns_left: u32,
+}
+
+/// The status effects currently on a unit.
+#[derive(Component, Reflect, Clone, Debug, Default)]
+pub struct StatusEffects(pub Vec<StatusEffect>);
+
+/// Counts down the status effects of every unit that takes a turn, removing
+/// those that wear off.
+pub(super) fn expire_status_effects(
+    mut units: Query<&mut StatusEffects>,
+    mut turns: EventReader<ValidatedTurn>,
+) {
+    for turn in turns.iter() {
+        let Ok(mut status_effects) = units.get_mut(turn.unit) else { continue };
+        for status_effect in status_effects.0.iter_mut() {
+            status_effect.turns_left = status_effect.turns_left.saturating_sub(1);
+        }
+        status_effects
+            .0
+            .retain(|status_effect| status_effect.turns_left > 0);
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoader, Error, LoadContext, LoadState, LoadedAsset},
+    ecs::system::SystemParam,
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use super::MovementType;
+
+const TERRAIN_DEFS_PATH: &str = "maps/levels.terrain.ron";
+
+/// How one kind of terrain behaves.
+#[derive(Deserialize, Clone, Debug)]
+pub struct TerrainDef {
+    pub name: String,
+    /// The IntGrid value this terrain is painted with in LDtk. Tiles with
+    /// nothing painted on them have the value 0.
+    pub int_grid_value: i32,
+    /// What entering this terrain costs each movement type. Movement types
+    /// missing here can't enter it.
+    pub move_costs: HashMap<MovementType, u32>,
+    /// Subtracted from damage taken by units on this terrain.
+    #[serde(default)]
+    pub defense_bonus: u32,
+    /// Subtracted from the chance to hit units on this terrain.
+    #[serde(default)]
+    pub evade_bonus: f32,
+    /// Added to damage dealt by units on this terrain.
+    #[serde(default)]
+    pub damage_bonus: i32,
+    /// Added to damage dealt by units on this terrain to adjacent targets, on
+    /// top of `damage_bonus`.
+    #[serde(default)]
+    pub melee_damage_bonus: i32,
+    #[serde(default)]
+    pub heal_per_turn: u32,
+    /// Dealt to units for every tile of this terrain they walk onto.
+    #[serde(default)]
+    pub damage_on_enter: u32,
+    #[serde(default)]
+    pub blocks_sight: bool,
+}
+
+#[derive(Deserialize, TypeUuid, Debug)]
+#[uuid = "5b0f6a3e-2d7c-4c1a-9e58-8a4f3b2d1c07"]
+pub struct TerrainDefs {
+    /// The LDtk IntGrid layer terrain is painted on.
+    pub layer: String,
+    pub terrains: Vec<TerrainDef>,
+}
+
+impl TerrainDefs {
+    pub fn get(&self, int_grid_value: i32) -> Option<&TerrainDef> {
+        self.terrains
+            .iter()
+            .find(|terrain| terrain.int_grid_value == int_grid_value)
+    }
+}
+
+#[derive(Default)]
+struct TerrainDefsLoader;
+
+impl AssetLoader for TerrainDefsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let terrain_defs: TerrainDefs = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(terrain_defs));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["terrain.ron"]
+    }
+}
+
+#[derive(Resource)]
+struct TerrainDefsHandle(Handle<TerrainDefs>);
+
+fn load_terrain_defs(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TerrainDefsHandle(asset_server.load(TERRAIN_DEFS_PATH)));
+}
+
+/// Without terrain definitions no tile can be entered, so a missing or broken
+/// file shouldn't go unnoticed.
+fn report_terrain_defs_failure(
+    asset_server: Res<AssetServer>,
+    handle: Res<TerrainDefsHandle>,
+    mut reported: Local<bool>,
+) {
+    if !*reported && asset_server.get_load_state(&handle.0) == LoadState::Failed {
+        error!("Failed to load terrain definitions from {TERRAIN_DEFS_PATH}");
+        *reported = true;
+    }
+}
+
+#[derive(SystemParam)]
+pub struct TerrainDefsParam<'w> {
+    handle: Option<Res<'w, TerrainDefsHandle>>,
+    terrain_defs: Res<'w, Assets<TerrainDefs>>,
+}
+
+impl<'w> TerrainDefsParam<'w> {
+    /// The terrain definitions, once they have loaded.
+    pub fn get(&self) -> Option<&TerrainDefs> {
+        self.terrain_defs.get(&self.handle.as_ref()?.0)
+    }
+}
+
+pub fn terrain_defs_loaded(terrain_defs_param: TerrainDefsParam) -> bool {
+    terrain_defs_param.get().is_some()
+}
+
+pub struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<TerrainDefs>()
+            .init_asset_loader::<TerrainDefsLoader>()
+            .add_startup_system(load_terrain_defs)
+            .add_system(report_terrain_defs_failure);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::logic::MovementType;
+
+    use super::TerrainDefs;
+
+    fn terrain_defs() -> TerrainDefs {
+        ron::from_str(include_str!("../../assets/maps/levels.terrain.ron"))
+            .expect("terrain definitions parse")
+    }
+
+    #[test]
+    fn only_flyers_and_swimmers_enter_water() {
+        let terrain_defs = terrain_defs();
+        let water = terrain_defs.get(1).expect("water is defined");
+        assert_eq!(water.move_costs.get(&MovementType::Foot), None);
+        assert_eq!(water.move_costs.get(&MovementType::Mounted), None);
+        assert_eq!(water.move_costs.get(&MovementType::Flying), Some(&1));
+        assert_eq!(water.move_costs.get(&MovementType::Aquatic), Some(&1));
+    }
+
+    #[test]
+    fn cavalry_pays_more_in_trees() {
+        let terrain_defs = terrain_defs();
+        let trees = terrain_defs.get(2).expect("trees are defined");
+        assert!(trees.move_costs[&MovementType::Mounted] > trees.move_costs[&MovementType::Foot]);
+    }
+}
@@ -1,22 +1,21 @@
+use std::collections::{HashMap, HashSet};
+
 use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_ecs_ldtk::IntGridCell;
 use bevy_ecs_tilemap::tiles::{TilePos, TileStorage};
 
-use crate::{SelectedUnit, TRPGState};
+use crate::{MoveDestination, SelectedUnit, TRPGState};
 
-use super::{get_attackable_tiles, reachable, UnitRange};
+use super::{
+    get_attackable_tiles, reachable, terrain_defs_loaded, MovementType, TerrainDef,
+    TerrainDefsParam, TerrainModifiers, UnitRange,
+};
 
-fn mark_reachable_tiles(
-    reachable_tiles_param: reachable::ReachableTilesParam,
-    mut reachable_info: Query<(&TilePos, &mut ReachableInfo)>,
-    unit_ranges: Query<&UnitRange>,
-    selected: Res<SelectedUnit>,
+fn set_reachable_info(
+    reachable_info: &mut Query<(&TilePos, &mut ReachableInfo)>,
+    reachable_tiles: &HashSet<TilePos>,
+    attack_movable_tiles: &HashSet<TilePos>,
 ) {
-    let reachable_tiles = reachable_tiles_param.get(selected.0).unwrap_or_default();
-    let attack_movable_tiles = unit_ranges
-        .get(selected.0)
-        .map(|unit_range| get_attackable_tiles(&reachable_tiles, &unit_range.valid_ranges))
-        .unwrap_or_default();
     for (tile_pos, mut reachable_info) in reachable_info.iter_mut() {
         let reachable = reachable_tiles.contains(tile_pos);
         let attack_movable = attack_movable_tiles.contains(tile_pos);
@@ -29,16 +28,76 @@ fn mark_reachable_tiles(
     }
 }
 
+fn mark_reachable_tiles(
+    reachable_tiles_param: reachable::ReachableTilesParam,
+    mut reachable_info: Query<(&TilePos, &mut ReachableInfo)>,
+    unit_ranges: Query<&UnitRange>,
+    selected: Res<SelectedUnit>,
+) {
+    let reachable_tiles = reachable_tiles_param.get(selected.0).unwrap_or_default();
+    let attack_movable_tiles = unit_ranges
+        .get(selected.0)
+        .map(|unit_range| get_attackable_tiles(&reachable_tiles, &unit_range.valid_ranges))
+        .unwrap_or_default();
+    set_reachable_info(&mut reachable_info, &reachable_tiles, &attack_movable_tiles);
+}
+
+/// Marks the tiles the selected unit can attack once it has moved.
+fn mark_attack_tiles(
+    mut reachable_info: Query<(&TilePos, &mut ReachableInfo)>,
+    unit_ranges: Query<&UnitRange>,
+    selected: Res<SelectedUnit>,
+    destination: Res<MoveDestination>,
+) {
+    let destination = HashSet::from([TilePos::new(destination.0.x as u32, destination.0.y as u32)]);
+    let attack_movable_tiles = unit_ranges
+        .get(selected.0)
+        .map(|unit_range| get_attackable_tiles(&destination, &unit_range.valid_ranges))
+        .unwrap_or_default();
+    set_reachable_info(&mut reachable_info, &HashSet::new(), &attack_movable_tiles);
+}
+
+fn clear_reachable_tiles(mut reachable_info: Query<(&TilePos, &mut ReachableInfo)>) {
+    set_reachable_info(&mut reachable_info, &HashSet::new(), &HashSet::new());
+}
+
 #[derive(Component, Default, Reflect)]
 pub(super) struct LogicTile {
-    pub(super) can_move: bool,
-    pub(super) move_cost: u32,
+    pub(super) terrain: String,
+    /// What entering this tile costs each movement type that can.
+    #[reflect(ignore)]
+    pub(super) move_costs: HashMap<MovementType, u32>,
+    pub(super) blocks_sight: bool,
+    pub(super) defense_bonus: u32,
+    pub(super) evade_bonus: f32,
+    pub(super) damage_bonus: i32,
+    pub(super) melee_damage_bonus: i32,
+    pub(super) heal_per_turn: u32,
+    pub(super) damage_on_enter: u32,
+}
+
+impl From<&TerrainDef> for LogicTile {
+    fn from(terrain_def: &TerrainDef) -> Self {
+        Self {
+            terrain: terrain_def.name.clone(),
+            move_costs: terrain_def.move_costs.clone(),
+            blocks_sight: terrain_def.blocks_sight,
+            defense_bonus: terrain_def.defense_bonus,
+            evade_bonus: terrain_def.evade_bonus,
+            damage_bonus: terrain_def.damage_bonus,
+            melee_damage_bonus: terrain_def.melee_damage_bonus,
+            heal_per_turn: terrain_def.heal_per_turn,
+            damage_on_enter: terrain_def.damage_on_enter,
+        }
+    }
 }
 
 #[derive(Component, Default, Reflect)]
 pub struct ReachableInfo {
     pub reachable: bool,
     pub attack_movable: bool,
+    /// On the path the selected unit would take to the tile under the cursor.
+    pub on_path: bool,
 }
 
 #[derive(Component, Default, Reflect)]
@@ -52,9 +111,11 @@ struct TileType;
 fn mark_tile_type_storage(
     mut commands: Commands,
     tile_storages: Query<(Entity, &Name), Added<TileStorage>>,
+    terrain_defs_param: TerrainDefsParam,
 ) {
+    let Some(terrain_defs) = terrain_defs_param.get() else { return };
     for (tile_storage, name) in tile_storages.iter() {
-        if name.as_str() == "TileType" {
+        if name.as_str() == terrain_defs.layer {
             commands.entity(tile_storage).insert(TileType);
         }
     }
@@ -67,24 +128,25 @@ struct TileExtraBundle {
     pub attackable_info: AttackableInfo,
 }
 
+/// Gives tiles the terrain their IntGrid value is defined as. Values without a
+/// definition can't be entered.
 fn populate_logic_tiles(
     mut commands: Commands,
-    tiles: Query<(Entity, &IntGridCell), Added<IntGridCell>>,
+    tiles: Query<(Entity, &IntGridCell), Without<LogicTile>>,
     other_tiles: Query<Entity, (Without<IntGridCell>, Without<LogicTile>)>,
     tile_maps: Query<&TileStorage, With<TileType>>,
+    terrain_defs_param: TerrainDefsParam,
 ) {
+    let Some(terrain_defs) = terrain_defs_param.get() else { return };
+    let logic_tile = |value| {
+        terrain_defs
+            .get(value)
+            .map(LogicTile::from)
+            .unwrap_or_default()
+    };
     for (entity, &IntGridCell { value }) in tiles.iter() {
         commands.entity(entity).insert(TileExtraBundle {
-            logic_tile: match value {
-                2 => LogicTile {
-                    can_move: true,
-                    move_cost: 2,
-                },
-                _ => LogicTile {
-                    can_move: false,
-                    move_cost: 0,
-                },
-            },
+            logic_tile: logic_tile(value),
             ..Default::default()
         });
     }
@@ -94,10 +156,7 @@ fn populate_logic_tiles(
                 continue;
             }
             commands.entity(tile).insert(TileExtraBundle {
-                logic_tile: LogicTile {
-                    can_move: true,
-                    move_cost: 1,
-                },
+                logic_tile: logic_tile(0),
                 ..Default::default()
             });
         }
@@ -115,19 +174,97 @@ impl<'w, 's> GetTileStorageParam<'w, 's> {
     }
 }
 
+#[derive(SystemParam)]
+pub struct LogicTileParam<'w, 's> {
+    logic_tiles: Query<'w, 's, &'static LogicTile>,
+    tile_storage: GetTileStorageParam<'w, 's>,
+}
+
+impl<'w, 's> LogicTileParam<'w, 's> {
+    fn get(&self, pos: IVec2) -> Option<&LogicTile> {
+        let tile_storage = self.tile_storage.get()?;
+        if pos.x < 0 || pos.y < 0 {
+            return None;
+        }
+        tile_storage
+            .checked_get(&TilePos::new(pos.x as u32, pos.y as u32))
+            .and_then(|tile| self.logic_tiles.get(tile).ok())
+    }
+
+    pub fn terrain_name(&self, pos: IVec2) -> Option<&str> {
+        self.get(pos).map(|logic_tile| logic_tile.terrain.as_str())
+    }
+
+    /// What entering `pos` costs `movement_type`, if it can at all.
+    pub fn move_cost(&self, pos: IVec2, movement_type: MovementType) -> Option<u32> {
+        self.get(pos)?.move_costs.get(&movement_type).copied()
+    }
+
+    pub fn can_move(&self, pos: IVec2, movement_type: MovementType) -> bool {
+        self.move_cost(pos, movement_type).is_some()
+    }
+
+    pub fn blocks_sight(&self, pos: IVec2) -> bool {
+        self.get(pos).is_none_or(|logic_tile| logic_tile.blocks_sight)
+    }
+
+    pub fn terrain_modifiers(&self, pos: IVec2) -> TerrainModifiers {
+        self.get(pos)
+            .map_or_else(TerrainModifiers::default, |logic_tile| TerrainModifiers {
+                defense_bonus: logic_tile.defense_bonus,
+                evade_bonus: logic_tile.evade_bonus,
+                damage_bonus: logic_tile.damage_bonus,
+                melee_damage_bonus: logic_tile.melee_damage_bonus,
+            })
+    }
+
+    pub fn heal_per_turn(&self, pos: IVec2) -> u32 {
+        self.get(pos).map_or(0, |logic_tile| logic_tile.heal_per_turn)
+    }
+
+    pub fn damage_on_enter(&self, pos: IVec2) -> u32 {
+        self.get(pos).map_or(0, |logic_tile| logic_tile.damage_on_enter)
+    }
+}
+
 pub struct TilePlugin;
 
 impl Plugin for TilePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            (
-                mark_tile_type_storage,
-                populate_logic_tiles,
-                mark_reachable_tiles,
+        app.add_system(mark_reachable_tiles.in_schedule(OnEnter(TRPGState::ChoosingMove)))
+            .add_system(
+                mark_reachable_tiles
+                    .run_if(resource_changed::<SelectedUnit>())
+                    .in_set(OnUpdate(TRPGState::ChoosingMove)),
+            )
+            .add_system(mark_attack_tiles.in_set(OnUpdate(TRPGState::ChoosingAttack)))
+            .add_system(clear_reachable_tiles.in_schedule(OnEnter(TRPGState::Battle)))
+            .add_systems(
+                (mark_tile_type_storage, populate_logic_tiles)
+                    .distributive_run_if(terrain_defs_loaded),
             )
-                .in_set(OnUpdate(TRPGState::Battle)),
-        )
-        .register_type::<LogicTile>()
-        .register_type::<ReachableInfo>();
+            .register_type::<LogicTile>()
+            .register_type::<ReachableInfo>();
+    }
+}
+
+/// Spawns a `size` map of foot-walkable plains for tests, with sight blocked
+/// on `walls`.
+#[cfg(test)]
+pub(crate) fn spawn_test_tiles(world: &mut World, size: UVec2, walls: &[IVec2]) {
+    let mut tile_storage = TileStorage::empty(size.into());
+    for x in 0..size.x {
+        for y in 0..size.y {
+            let tile = world
+                .spawn(LogicTile {
+                    terrain: "Plain".to_string(),
+                    move_costs: HashMap::from([(MovementType::Foot, 1)]),
+                    blocks_sight: walls.contains(&IVec2::new(x as i32, y as i32)),
+                    ..Default::default()
+                })
+                .id();
+            tile_storage.set(&TilePos::new(x, y), tile);
+        }
     }
+    world.spawn((tile_storage, TileType));
 }
@@ -0,0 +1,101 @@
+use std::cmp::Reverse;
+
+use bevy::prelude::*;
+
+use super::UnitStats;
+
+/// A predicted turn: `unit` gets to act `ticks` logical ticks from now.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Activation {
+    pub unit: Entity,
+    pub ticks: u64,
+}
+
+/// Predicts the next `count` turns from each unit's entity, current
+/// initiative and stats, assuming every unit acts as soon as it is ready and
+/// each later turn uses up `later_turn_cost` of its bar. Ties are broken like
+/// `turn_order_key`.
+pub fn predict_turn_order<'a>(
+    units: impl IntoIterator<Item = (Entity, f32, &'a UnitStats)>,
+    later_turn_cost: f32,
+    count: usize,
+) -> Vec<Activation> {
+    let mut activations = Vec::new();
+    for (unit, initiative, unit_stats) in units {
+        let first = unit_stats.ticks_to_fill(initiative);
+        let period = unit_stats
+            .ticks_to_fill(unit_stats.max_initiative * (1.0 - later_turn_cost))
+            .max(1);
+        if first == u64::MAX {
+            continue;
+        }
+        activations.extend(
+            (0..count as u64)
+                .map(|turn| first.saturating_add(turn.saturating_mul(period)))
+                .map(|ticks| (ticks, Reverse(unit_stats.agility), unit.index(), unit)),
+        );
+    }
+    activations.sort();
+    activations
+        .into_iter()
+        .take(count)
+        .map(|(ticks, _, _, unit)| Activation { unit, ticks })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Entity;
+
+    use crate::logic::{UnitStats, BASE_AGILITY};
+
+    use super::predict_turn_order;
+
+    fn stats(agility: u32) -> UnitStats {
+        UnitStats {
+            max_hp: 5,
+            max_initiative: 5.0,
+            base_atk: 3,
+            base_armor: 2,
+            agility,
+        }
+    }
+
+    #[test]
+    fn faster_units_act_more_often() {
+        let fast = Entity::from_raw(0);
+        let slow = Entity::from_raw(1);
+        let fast_stats = stats(BASE_AGILITY * 2);
+        let slow_stats = stats(BASE_AGILITY);
+        let order: Vec<_> =
+            predict_turn_order([(slow, 0.0, &slow_stats), (fast, 0.0, &fast_stats)], 1.0, 4)
+                .into_iter()
+                .map(|activation| activation.unit)
+                .collect();
+        assert_eq!(order, vec![fast, fast, slow, fast]);
+    }
+
+    #[test]
+    fn ready_units_act_first() {
+        let ready = Entity::from_raw(1);
+        let waiting = Entity::from_raw(0);
+        let unit_stats = stats(BASE_AGILITY);
+        let order = predict_turn_order(
+            [(waiting, 2.0, &unit_stats), (ready, 5.0, &unit_stats)],
+            1.0,
+            2,
+        );
+        assert_eq!(order[0].unit, ready);
+        assert_eq!(order[0].ticks, 0);
+        assert_eq!(order[1].unit, waiting);
+    }
+
+    #[test]
+    fn cheaper_turns_come_around_sooner() {
+        let unit = Entity::from_raw(0);
+        let unit_stats = stats(BASE_AGILITY);
+        let full = predict_turn_order([(unit, 5.0, &unit_stats)], 1.0, 2);
+        let half = predict_turn_order([(unit, 5.0, &unit_stats)], 0.5, 2);
+        assert!(half[1].ticks < full[1].ticks);
+    }
+}
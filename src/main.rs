@@ -1,56 +1,43 @@
+use battle_log::BattleLogPlugin;
 use bevy::{core_pipeline::clear_color::ClearColorConfig, prelude::*};
 use bevy_ecs_ldtk::{LdtkWorldBundle, LevelSelection};
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
-use cursor::{CursorPlugin, CursorPos};
-use logic::{
-    GridPosition, LogicPlugin, ReachableInfo, Unit, UnitAction, UnitLogicBundle, UnitRange,
-    UnitSpeed, UnitStats, UnitTurn,
-};
-use progress_bar::{initiative_progress_bar_bundle, ProgressBarPlugin};
+use cursor::CursorPlugin;
+use death_display::DeathDisplayPlugin;
+use deployment::DeploymentPlugin;
+use forecast_panel::ForecastPanelPlugin;
+use logic::{GridPosition, LogicPlugin, ReachableInfo};
+use movement_animation::{MoveAnimation, MovementAnimationPlugin};
+use progress_bar::ProgressBarPlugin;
+use tile_info_panel::TileInfoPanelPlugin;
+use timeline::TimelinePlugin;
+use turn_input::TurnInputPlugin;
+use unit_card::UnitCardPlugin;
+use units::UnitsPlugin;
+use waves::WavesPlugin;
 
+mod battle_log;
 mod cursor;
+mod death_display;
+mod deployment;
+mod forecast_panel;
 mod logic;
+mod movement_animation;
 mod progress_bar;
+mod tile_info_panel;
+mod timeline;
+mod turn_input;
+mod unit_card;
+mod units;
+mod waves;
+mod zone;
 
 const GRID_SIZE: f32 = 16.0;
 #[derive(Resource)]
 struct SelectedUnit(Entity);
-
-fn add_unit(commands: &mut Commands) -> Entity {
-    commands
-        .spawn((
-            UnitLogicBundle {
-                unit: Unit {
-                    initiative: 0.0,
-                    current_hp: 5,
-                },
-                unit_stats: UnitStats {
-                    max_hp: 5,
-                    max_initiative: 5.0,
-                    base_atk: 3,
-                    base_armor: 2,
-                },
-                unit_speed: UnitSpeed(5),
-                unit_range: UnitRange {
-                    valid_ranges: vec![2],
-                },
-                grid_position: GridPosition(IVec2 { x: 3, y: 5 }),
-            },
-            SpriteBundle {
-                transform: Transform::from_translation(Vec3::new(0.0, 0.0, 2.0)),
-                sprite: Sprite {
-                    color: Color::rgb(0.5, 0.4, 0.3),
-                    custom_size: Some(Vec2::new(GRID_SIZE, GRID_SIZE)),
-                    ..Default::default()
-                },
-                ..Default::default()
-            },
-        ))
-        .with_children(|parent| {
-            parent.spawn(initiative_progress_bar_bundle());
-        })
-        .id()
-}
+/// Where the selected unit will move to, once chosen.
+#[derive(Resource)]
+struct MoveDestination(IVec2);
 
 fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.spawn(LdtkWorldBundle {
@@ -68,11 +55,11 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         },
         ..Default::default()
     });
-    let unit = add_unit(&mut commands);
-    commands.insert_resource(SelectedUnit(unit));
 }
 
-fn update_grid_transform(mut query: Query<(&GridPosition, &mut Transform)>) {
+fn update_grid_transform(
+    mut query: Query<(&GridPosition, &mut Transform), Without<MoveAnimation>>,
+) {
     for (grid_position, mut transform) in query.iter_mut() {
         transform.translation = Vec3::new(
             (grid_position.0.x as f32 + 0.5) * GRID_SIZE,
@@ -82,35 +69,13 @@ fn update_grid_transform(mut query: Query<(&GridPosition, &mut Transform)>) {
     }
 }
 
-fn mouse_movement(
-    mut turns: EventWriter<UnitTurn>,
-    units: Query<&GridPosition>,
-    buttons: Res<Input<MouseButton>>,
-    cursor: Res<CursorPos>,
-    selected_unit: Res<SelectedUnit>,
-) {
-    if buttons.just_pressed(MouseButton::Left) {
-        if let Ok(pos) = units.get(selected_unit.0) {
-            let cursor_tile_pos = cursor.0 / GRID_SIZE;
-            let cursor_tile_pos_snapped = IVec2::new(
-                cursor_tile_pos.x.floor() as i32,
-                cursor_tile_pos.y.floor() as i32,
-            );
-            turns.send(UnitTurn {
-                unit: selected_unit.0,
-                start_position: pos.0,
-                end_position: cursor_tile_pos_snapped,
-                action: UnitAction::Wait,
-            });
-        }
-    }
-}
-
 #[derive(Component)]
 struct ReachableDisplay;
 
 const REACHABLE_COLOR: Color = Color::rgba(77.0 / 255.0, 90.0 / 255.0, 200.0 / 255.0, 80.0 / 255.0);
 
+const PATH_COLOR: Color = Color::rgba(230.0 / 255.0, 220.0 / 255.0, 120.0 / 255.0, 140.0 / 255.0);
+
 const ATTACKABLE_COLOR: Color =
     Color::rgba(200.0 / 255.0, 90.0 / 255.0, 77.0 / 255.0, 80.0 / 255.0);
 
@@ -145,7 +110,10 @@ fn update_reachable_display(
 ) {
     for (display, mut sprite, mut visibility, parent) in displays.iter_mut() {
         let Ok(reachable_info) = tiles.get(parent.get()) else { commands.entity(display).despawn_recursive(); continue; };
-        if reachable_info.reachable {
+        if reachable_info.on_path {
+            sprite.color = PATH_COLOR;
+            *visibility = Default::default();
+        } else if reachable_info.reachable {
             sprite.color = REACHABLE_COLOR;
             *visibility = Default::default();
         } else if reachable_info.attack_movable {
@@ -160,9 +128,12 @@ fn update_reachable_display(
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, States)]
 pub enum TRPGState {
     #[default]
+    Deployment,
     Battle,
     ChoosingMove,
     ChoosingAttack,
+    Victory,
+    Defeat,
 }
 
 fn main() {
@@ -174,16 +145,23 @@ fn main() {
         .add_plugin(CursorPlugin)
         .add_plugin(LogicPlugin)
         .add_plugin(ProgressBarPlugin)
+        .add_plugin(UnitsPlugin)
+        .add_plugin(DeploymentPlugin)
+        .add_plugin(WavesPlugin)
+        .add_plugin(DeathDisplayPlugin)
+        .add_plugin(MovementAnimationPlugin)
+        .add_plugin(BattleLogPlugin)
+        .add_plugin(ForecastPanelPlugin)
+        .add_plugin(TurnInputPlugin)
+        .add_plugin(TimelinePlugin)
+        .add_plugin(TileInfoPanelPlugin)
+        .add_plugin(UnitCardPlugin)
         .add_startup_system(setup)
         .insert_resource(LevelSelection::Index(0))
-        .add_systems(
-            (
-                update_grid_transform,
-                add_reachable_display,
-                update_reachable_display,
-                mouse_movement,
-            )
-                .in_set(OnUpdate(TRPGState::Battle)),
-        )
+        .add_systems((
+            update_grid_transform,
+            add_reachable_display,
+            update_reachable_display,
+        ))
         .run();
 }
@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+
+use crate::{
+    logic::{MovementCompleted, UnitMoved},
+    GRID_SIZE,
+};
+
+#[derive(Resource)]
+pub struct MoveAnimationSettings {
+    pub tiles_per_second: f32,
+}
+
+impl Default for MoveAnimationSettings {
+    fn default() -> Self {
+        Self {
+            tiles_per_second: 8.0,
+        }
+    }
+}
+
+/// Walks a unit's sprite along its path, one tile at a time. While present,
+/// the sprite no longer follows its `GridPosition`.
+#[derive(Component)]
+pub struct MoveAnimation {
+    path: Vec<IVec2>,
+    /// How many tiles along the path the sprite has moved.
+    progress: f32,
+}
+
+fn tile_center(pos: IVec2) -> Vec2 {
+    (pos.as_vec2() + 0.5) * GRID_SIZE
+}
+
+fn start_move_animation(
+    mut commands: Commands,
+    mut unit_moved: EventReader<UnitMoved>,
+    mut movement_completed: EventWriter<MovementCompleted>,
+) {
+    for UnitMoved { unit, path } in unit_moved.iter() {
+        if path.len() < 2 {
+            movement_completed.send(MovementCompleted { unit: *unit });
+            continue;
+        }
+        let Some(mut entity_commands) = commands.get_entity(*unit) else { continue };
+        entity_commands.insert(MoveAnimation {
+            path: path.clone(),
+            progress: 0.0,
+        });
+    }
+}
+
+fn animate_movement(
+    mut commands: Commands,
+    mut animations: Query<(Entity, &mut MoveAnimation, &mut Transform)>,
+    mut movement_completed: EventWriter<MovementCompleted>,
+    settings: Res<MoveAnimationSettings>,
+    time: Res<Time>,
+) {
+    for (unit, mut animation, mut transform) in animations.iter_mut() {
+        let last_step = (animation.path.len() - 1) as f32;
+        animation.progress =
+            (animation.progress + settings.tiles_per_second * time.delta_seconds()).min(last_step);
+        let step = animation.progress.floor() as usize;
+        let position = match animation.path.get(step + 1) {
+            Some(&next) => tile_center(animation.path[step])
+                .lerp(tile_center(next), animation.progress.fract()),
+            None => tile_center(animation.path[step]),
+        };
+        transform.translation = position.extend(transform.translation.z);
+        if animation.progress >= last_step {
+            commands.entity(unit).remove::<MoveAnimation>();
+            movement_completed.send(MovementCompleted { unit });
+        }
+    }
+}
+
+/// Whether no unit is walking along its path.
+pub fn no_unit_moving(moving: Query<(), With<MoveAnimation>>) -> bool {
+    moving.is_empty()
+}
+
+pub struct MovementAnimationPlugin;
+
+impl Plugin for MovementAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MoveAnimationSettings>()
+            .add_systems((start_move_animation, animate_movement));
+    }
+}
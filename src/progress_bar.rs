@@ -1,14 +1,11 @@
 use bevy::prelude::*;
 
-use crate::{
-    logic::{Unit, UnitStats},
-    TRPGState,
-};
+use crate::logic::{battle_in_progress, Unit, UnitStats};
 
 const PROGRESS_BAR_WIDTH: f32 = 16.0;
 const PROGRESS_BAR_HEIGHT: f32 = 4.0;
 
-pub fn initiative_progress_bar_bundle() -> impl Bundle {
+fn initiative_progress_bar_bundle() -> impl Bundle {
     (
         InitiativeProgressBar,
         ProgressBar::default(),
@@ -32,6 +29,14 @@ struct ProgressBar {
 #[derive(Component)]
 struct InitiativeProgressBar;
 
+fn add_initiative_progress_bar(mut commands: Commands, units: Query<Entity, Added<Unit>>) {
+    for unit in units.iter() {
+        commands.entity(unit).with_children(|parent| {
+            parent.spawn(initiative_progress_bar_bundle());
+        });
+    }
+}
+
 fn update_initiative_progress_bar(
     q_parent: Query<(&Unit, &UnitStats, &Children)>,
     mut q_child: Query<&mut ProgressBar, With<InitiativeProgressBar>>,
@@ -57,9 +62,9 @@ pub struct ProgressBarPlugin;
 
 impl Plugin for ProgressBarPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.add_system(add_initiative_progress_bar).add_systems(
             (update_initiative_progress_bar, update_progress_bar_sprite)
-                .in_set(OnUpdate(TRPGState::Battle)),
+                .distributive_run_if(battle_in_progress),
         );
     }
 }
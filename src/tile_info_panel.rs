@@ -0,0 +1,123 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_ecs_tilemap::tiles::TilePos;
+use bevy_egui::{
+    egui::{self, Align2},
+    EguiContexts, EguiPlugin,
+};
+
+use crate::{
+    cursor::CursorPos,
+    logic::{
+        GetTileStorageParam, GridPosition, LogicTileParam, MovementType, ReachableInfo, Unit,
+        UnitStats,
+    },
+    TRPGState,
+};
+
+#[derive(SystemParam)]
+struct HoveredTileParam<'w, 's> {
+    cursor: Res<'w, CursorPos>,
+    logic_tile_param: LogicTileParam<'w, 's>,
+    tile_storage: GetTileStorageParam<'w, 's>,
+    reachable_info: Query<'w, 's, &'static ReachableInfo>,
+}
+
+impl<'w, 's> HoveredTileParam<'w, 's> {
+    fn reachable_info(&self, pos: IVec2) -> Option<&ReachableInfo> {
+        if pos.x < 0 || pos.y < 0 {
+            return None;
+        }
+        let tile = self
+            .tile_storage
+            .get()?
+            .checked_get(&TilePos::new(pos.x as u32, pos.y as u32))?;
+        self.reachable_info.get(tile).ok()
+    }
+}
+
+fn show_tile_info_panel(
+    mut contexts: EguiContexts,
+    hovered_tile_param: HoveredTileParam,
+    units: Query<(&Unit, &UnitStats, &GridPosition, Option<&Name>)>,
+    state: Res<State<TRPGState>>,
+) {
+    let pos = hovered_tile_param.cursor.grid_position();
+    let logic_tile_param = &hovered_tile_param.logic_tile_param;
+    let Some(terrain) = logic_tile_param.terrain_name(pos) else { return };
+    let modifiers = logic_tile_param.terrain_modifiers(pos);
+    let heal_per_turn = logic_tile_param.heal_per_turn(pos);
+    let damage_on_enter = logic_tile_param.damage_on_enter(pos);
+    let move_costs: Vec<_> = MovementType::ALL
+        .into_iter()
+        .map(
+            |movement_type| match logic_tile_param.move_cost(pos, movement_type) {
+                Some(cost) => format!("{movement_type:?} {cost}"),
+                None => format!("{movement_type:?} -"),
+            },
+        )
+        .collect();
+    // Tiles are only marked while the selected unit's orders are chosen.
+    let reachable_info = matches!(state.0, TRPGState::ChoosingMove | TRPGState::ChoosingAttack)
+        .then(|| hovered_tile_param.reachable_info(pos))
+        .flatten();
+    let occupant = units.iter().find(|(_, _, unit_pos, _)| unit_pos.0 == pos);
+
+    egui::Window::new("Tile")
+        .id(egui::Id::new("tile_info"))
+        .anchor(Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+        .resizable(false)
+        .collapsible(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.strong(terrain);
+            ui.label(format!("Move cost: {}", move_costs.join(", ")));
+            if modifiers.defense_bonus > 0 {
+                ui.label(format!("Defense: +{}", modifiers.defense_bonus));
+            }
+            if modifiers.evade_bonus != 0.0 {
+                ui.label(format!("Evade: {:+.0}%", modifiers.evade_bonus * 100.0));
+            }
+            if modifiers.damage_bonus != 0 {
+                ui.label(format!("Damage: {:+}", modifiers.damage_bonus));
+            }
+            if modifiers.melee_damage_bonus != 0 {
+                ui.label(format!("Melee damage: {:+}", modifiers.melee_damage_bonus));
+            }
+            if heal_per_turn > 0 {
+                ui.label(format!("Heals {heal_per_turn} HP per turn"));
+            }
+            if damage_on_enter > 0 {
+                ui.label(format!("Deals {damage_on_enter} damage on entry"));
+            }
+            if let Some(reachable_info) = reachable_info {
+                if reachable_info.reachable {
+                    ui.label("Reachable");
+                }
+                if reachable_info.attack_movable {
+                    ui.label("In attack range");
+                }
+            }
+            let Some((unit, unit_stats, _, name)) = occupant else { return };
+            ui.separator();
+            ui.strong(name.map_or("Unit", |name| name.as_str()));
+            ui.label(format!("HP: {}/{}", unit.current_hp, unit_stats.max_hp));
+            ui.label(format!(
+                "Atk: {}  Armor: {}  Agility: {}",
+                unit_stats.base_atk, unit_stats.base_armor, unit_stats.agility
+            ));
+            ui.label(format!(
+                "Initiative: {:.1}/{:.1}",
+                unit.initiative, unit_stats.max_initiative
+            ));
+        });
+}
+
+pub struct TileInfoPanelPlugin;
+
+impl Plugin for TileInfoPanelPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugin(EguiPlugin);
+        }
+        app.add_system(show_tile_info_panel);
+    }
+}
@@ -0,0 +1,159 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_ecs_tilemap::tiles::TilePos;
+use bevy_egui::{
+    egui::{self, Align2, Color32, RichText},
+    EguiContexts, EguiPlugin,
+};
+
+use crate::{
+    cursor::CursorPos,
+    logic::{
+        battle_in_progress, predict_turn_order, ActionCosts, Faction, FactionRelations,
+        GridPosition, ReachableTilesParam, Unit, UnitAction, UnitStats, UnitTurn, TICK_SECONDS,
+    },
+    turn_input::heavy_attack_held,
+    MoveDestination, SelectedUnit, TRPGState,
+};
+
+const PREDICTED_TURNS: usize = 8;
+
+const PLAYER_COLOR: Color32 = Color32::from_rgb(120, 140, 240);
+const HOSTILE_COLOR: Color32 = Color32::from_rgb(240, 120, 100);
+const OTHER_COLOR: Color32 = Color32::from_rgb(200, 200, 200);
+
+/// The turn the selected unit would take if the player confirmed what is
+/// under the cursor, and the path it would walk.
+#[derive(SystemParam)]
+struct PreviewTurnParam<'w, 's> {
+    state: Res<'w, State<TRPGState>>,
+    selected_unit: Option<Res<'w, SelectedUnit>>,
+    destination: Option<Res<'w, MoveDestination>>,
+    cursor: Res<'w, CursorPos>,
+    keys: Res<'w, Input<KeyCode>>,
+    units: Query<'w, 's, (Entity, &'static GridPosition, &'static Faction)>,
+    relations: Res<'w, FactionRelations>,
+    reachable_tiles_param: ReachableTilesParam<'w, 's>,
+}
+
+impl<'w, 's> PreviewTurnParam<'w, 's> {
+    fn get(&self) -> Option<(UnitTurn, Vec<IVec2>)> {
+        let unit = self.selected_unit.as_ref()?.0;
+        let (_, start_pos, &faction) = self.units.get(unit).ok()?;
+        let hovered = self.cursor.grid_position();
+        let (end_position, action) = match self.state.0 {
+            TRPGState::ChoosingMove => (hovered, UnitAction::Wait),
+            TRPGState::ChoosingAttack => {
+                let destination = self.destination.as_ref()?.0;
+                let target = self.units.iter().find(|(_, pos, target_faction)| {
+                    pos.0 == hovered && self.relations.is_hostile(faction, **target_faction)
+                });
+                match target {
+                    Some((target, ..)) => {
+                        let heavy = heavy_attack_held(&self.keys);
+                        (destination, UnitAction::Attack { target, heavy })
+                    }
+                    None => (destination, UnitAction::Wait),
+                }
+            }
+            _ => return None,
+        };
+        if end_position.x < 0 || end_position.y < 0 {
+            return None;
+        }
+        let path = self
+            .reachable_tiles_param
+            .get_map(unit)?
+            .path_to(&TilePos::new(end_position.x as u32, end_position.y as u32))?;
+        let turn = UnitTurn {
+            unit,
+            start_position: start_pos.0,
+            end_position,
+            action,
+        };
+        Some((
+            turn,
+            path.into_iter()
+                .map(|tile| IVec2::new(tile.x as i32, tile.y as i32))
+                .collect(),
+        ))
+    }
+}
+
+fn show_timeline(
+    mut contexts: EguiContexts,
+    units: Query<(Entity, &Unit, &UnitStats, &Faction, Option<&Name>)>,
+    relations: Res<FactionRelations>,
+    preview_turn_param: PreviewTurnParam,
+    action_costs: Res<ActionCosts>,
+) {
+    // While orders are being chosen, preview the order after the selected unit
+    // has taken the turn under the cursor.
+    let preview_turn = preview_turn_param.get();
+    // Later turns are assumed to move and wait.
+    let activations = predict_turn_order(
+        units.iter().map(|(entity, unit, unit_stats, ..)| {
+            let initiative = match &preview_turn {
+                Some((turn, path)) if turn.unit == entity => {
+                    action_costs.initiative_after(turn.action, path, unit_stats)
+                }
+                _ => unit.initiative,
+            };
+            (entity, initiative, unit_stats)
+        }),
+        action_costs.wait,
+        PREDICTED_TURNS,
+    );
+    let title = match preview_turn.and_then(|(turn, _)| Some((turn, units.get(turn.unit).ok()?))) {
+        Some((turn, (.., Some(name)))) => match turn.action {
+            UnitAction::Wait => format!("Turn Order (if {name} waits)"),
+            UnitAction::Attack { heavy: false, .. } => format!("Turn Order (if {name} attacks)"),
+            UnitAction::Attack { heavy: true, .. } => {
+                format!("Turn Order (if {name} attacks heavily)")
+            }
+        },
+        _ => "Turn Order".to_string(),
+    };
+
+    egui::Window::new(title)
+        .id(egui::Id::new("turn_order"))
+        .anchor(Align2::LEFT_BOTTOM, [8.0, -8.0])
+        .resizable(false)
+        .collapsible(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                for activation in activations {
+                    let Ok((_, _, _, &faction, name)) = units.get(activation.unit) else { continue };
+                    let color = if faction == Faction::Player {
+                        PLAYER_COLOR
+                    } else if relations.is_hostile(Faction::Player, faction) {
+                        HOSTILE_COLOR
+                    } else {
+                        OTHER_COLOR
+                    };
+                    ui.vertical(|ui| {
+                        ui.label(
+                            RichText::new(name.map_or("Unit", |name| name.as_str())).color(color),
+                        );
+                        // Only player units are given turns yet, so anyone
+                        // else with a full bar holds it until they are.
+                        if activation.ticks == 0 && faction != Faction::Player {
+                            ui.label("ready");
+                        } else {
+                            ui.label(format!("+{:.1}s", activation.ticks as f32 * TICK_SECONDS));
+                        }
+                    });
+                }
+            });
+        });
+}
+
+pub struct TimelinePlugin;
+
+impl Plugin for TimelinePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugin(EguiPlugin);
+        }
+        app.add_system(show_timeline.run_if(battle_in_progress));
+    }
+}
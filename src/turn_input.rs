@@ -0,0 +1,329 @@
+use std::collections::HashSet;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_ecs_tilemap::tiles::TilePos;
+
+use crate::{
+    cursor::CursorPos,
+    logic::{
+        battle_in_progress, in_range, turn_order_key, AtbMode, Faction, FactionRelations,
+        GridPosition, LineOfSightParam, ReachableInfo, ReachableTilesParam, Unit, UnitAction,
+        UnitRange, UnitStats, UnitTurn,
+    },
+    movement_animation::no_unit_moving,
+    MoveDestination, SelectedUnit, TRPGState,
+};
+
+#[derive(SystemParam)]
+struct TurnInputParam<'w> {
+    buttons: Res<'w, Input<MouseButton>>,
+    keys: Res<'w, Input<KeyCode>>,
+    cursor: Res<'w, CursorPos>,
+}
+
+impl<'w> TurnInputParam<'w> {
+    /// The tile that was just left-clicked, if any.
+    fn clicked_tile(&self) -> Option<IVec2> {
+        self.buttons
+            .just_pressed(MouseButton::Left)
+            .then(|| self.cursor.grid_position())
+    }
+
+    fn back_pressed(&self) -> bool {
+        self.buttons.just_pressed(MouseButton::Right) || self.keys.just_pressed(KeyCode::Escape)
+    }
+
+    fn wait_pressed(&self) -> bool {
+        self.keys.just_pressed(KeyCode::Space)
+    }
+
+    fn next_pressed(&self) -> bool {
+        self.keys.just_pressed(KeyCode::Tab)
+    }
+}
+
+/// Holding Shift while choosing a target makes the attack a heavy one.
+pub fn heavy_attack_held(keys: &Input<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::LShift, KeyCode::RShift])
+}
+
+/// Living player units whose initiative is full.
+#[derive(SystemParam)]
+struct ReadyUnitsParam<'w, 's> {
+    units: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Unit,
+            &'static UnitStats,
+            &'static GridPosition,
+            &'static Faction,
+        ),
+    >,
+}
+
+impl<'w, 's> ReadyUnitsParam<'w, 's> {
+    /// Ready units with their positions, in turn order.
+    fn get(&self) -> Vec<(Entity, IVec2)> {
+        let mut ready_units: Vec<_> = self
+            .units
+            .iter()
+            .filter(|(_, unit, unit_stats, _, faction)| {
+                **faction == Faction::Player && unit.current_hp > 0 && unit.is_ready(unit_stats)
+            })
+            .collect();
+        ready_units.sort_by_key(|(entity, unit, unit_stats, ..)| {
+            turn_order_key(*entity, unit, unit_stats)
+        });
+        ready_units
+            .into_iter()
+            .map(|(entity, _, _, pos, _)| (entity, pos.0))
+            .collect()
+    }
+
+    fn at(&self, pos: IVec2) -> Option<Entity> {
+        self.get()
+            .into_iter()
+            .find(|(_, unit_pos)| *unit_pos == pos)
+            .map(|(entity, _)| entity)
+    }
+
+    /// The ready unit after `current`, wrapping around.
+    fn after(&self, current: Option<Entity>) -> Option<Entity> {
+        let ready_units = self.get();
+        let next = current
+            .and_then(|current| {
+                ready_units
+                    .iter()
+                    .position(|(entity, _)| *entity == current)
+            })
+            .map_or(0, |index| index + 1);
+        ready_units
+            .get(next)
+            .or_else(|| ready_units.first())
+            .map(|(entity, _)| *entity)
+    }
+}
+
+/// Clicking a ready unit, or cycling to one with Tab, selects it and starts
+/// choosing its move.
+fn select_unit(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<TRPGState>>,
+    ready_units_param: ReadyUnitsParam,
+    input: TurnInputParam,
+) {
+    let unit = if input.next_pressed() {
+        ready_units_param.after(None)
+    } else if let Some(pos) = input.clicked_tile() {
+        ready_units_param.at(pos)
+    } else {
+        None
+    };
+    let Some(unit) = unit else { return };
+    commands.insert_resource(SelectedUnit(unit));
+    next_state.set(TRPGState::ChoosingMove);
+}
+
+/// Selects units as their initiative fills up, each only once per readiness,
+/// so backing out of a unit's turn doesn't immediately select it again.
+fn auto_select_unit(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<TRPGState>>,
+    ready_units_param: ReadyUnitsParam,
+    mut offered: Local<HashSet<Entity>>,
+) {
+    let ready_units = ready_units_param.get();
+    offered.retain(|unit| ready_units.iter().any(|(entity, _)| entity == unit));
+    let Some(&(unit, _)) = ready_units.iter().find(|(entity, _)| !offered.contains(entity)) else { return };
+    offered.insert(unit);
+    commands.insert_resource(SelectedUnit(unit));
+    next_state.set(TRPGState::ChoosingMove);
+}
+
+/// Clicking a reachable tile picks the destination, while Tab or clicking
+/// another ready unit switches the selection.
+fn choose_move(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<TRPGState>>,
+    reachable_tiles_param: ReachableTilesParam,
+    ready_units_param: ReadyUnitsParam,
+    input: TurnInputParam,
+    selected_unit: Res<SelectedUnit>,
+) {
+    if input.back_pressed() {
+        next_state.set(TRPGState::Battle);
+        return;
+    }
+    if input.next_pressed() {
+        if let Some(unit) = ready_units_param.after(Some(selected_unit.0)) {
+            commands.insert_resource(SelectedUnit(unit));
+        }
+        return;
+    }
+    let Some(pos) = input.clicked_tile() else { return };
+    if let Some(unit) = ready_units_param
+        .at(pos)
+        .filter(|&unit| unit != selected_unit.0)
+    {
+        commands.insert_resource(SelectedUnit(unit));
+        return;
+    }
+    if pos.x < 0 || pos.y < 0 {
+        return;
+    }
+    let reachable_tiles = reachable_tiles_param
+        .get(selected_unit.0)
+        .unwrap_or_default();
+    if !reachable_tiles.contains(&TilePos::new(pos.x as u32, pos.y as u32)) {
+        return;
+    }
+    commands.insert_resource(MoveDestination(pos));
+    next_state.set(TRPGState::ChoosingAttack);
+}
+
+/// Highlights the route the selected unit would take to the tile under the
+/// cursor.
+fn mark_path_to_cursor(
+    mut reachable_info: Query<(&TilePos, &mut ReachableInfo)>,
+    reachable_tiles_param: ReachableTilesParam,
+    cursor: Res<CursorPos>,
+    selected_unit: Res<SelectedUnit>,
+) {
+    let hovered = cursor.grid_position();
+    let path = reachable_tiles_param
+        .get_map(selected_unit.0)
+        .filter(|_| hovered.x >= 0 && hovered.y >= 0)
+        .and_then(|reachable_map| {
+            let hovered = TilePos::new(hovered.x as u32, hovered.y as u32);
+            reachable_map
+                .contains(&hovered)
+                .then(|| reachable_map.path_to(&hovered))
+                .flatten()
+        })
+        .unwrap_or_default();
+    for (tile_pos, mut reachable_info) in reachable_info.iter_mut() {
+        let on_path = path.contains(tile_pos);
+        if reachable_info.on_path != on_path {
+            reachable_info.on_path = on_path;
+        }
+    }
+}
+
+fn clear_path(mut reachable_info: Query<&mut ReachableInfo>) {
+    for mut reachable_info in reachable_info.iter_mut() {
+        if reachable_info.on_path {
+            reachable_info.on_path = false;
+        }
+    }
+}
+
+#[derive(SystemParam)]
+struct AttackTargetParam<'w, 's> {
+    units: Query<
+        'w,
+        's,
+        (
+            &'static Unit,
+            &'static GridPosition,
+            &'static Faction,
+            &'static UnitRange,
+        ),
+    >,
+    targets: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Unit,
+            &'static GridPosition,
+            &'static Faction,
+        ),
+    >,
+    relations: Res<'w, FactionRelations>,
+    line_of_sight_param: LineOfSightParam<'w, 's>,
+}
+
+impl<'w, 's> AttackTargetParam<'w, 's> {
+    /// The living hostile unit on `pos`, if `attacker` can reach and see it
+    /// from `from`.
+    fn get(&self, attacker: Entity, from: IVec2, pos: IVec2) -> Option<Entity> {
+        let (_, _, &faction, unit_range) = self.units.get(attacker).ok()?;
+        if !in_range(&unit_range.valid_ranges, from, pos)
+            || !self.line_of_sight_param.has_line_of_sight(from, pos)
+        {
+            return None;
+        }
+        self.targets
+            .iter()
+            .find(|(_, unit, target_pos, target_faction)| {
+                target_pos.0 == pos
+                    && unit.current_hp > 0
+                    && self.relations.is_hostile(faction, **target_faction)
+            })
+            .map(|(target, ..)| target)
+    }
+}
+
+/// Clicking a hostile unit in range attacks it, heavily if Shift is held, while
+/// clicking the destination again or pressing Space waits there.
+fn choose_attack(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<TRPGState>>,
+    mut turns: EventWriter<UnitTurn>,
+    attack_target_param: AttackTargetParam,
+    input: TurnInputParam,
+    selected_unit: Res<SelectedUnit>,
+    destination: Res<MoveDestination>,
+) {
+    if input.back_pressed() {
+        commands.remove_resource::<MoveDestination>();
+        next_state.set(TRPGState::ChoosingMove);
+        return;
+    }
+    let Ok((_, start_pos, ..)) = attack_target_param.units.get(selected_unit.0) else { return };
+    let clicked_tile = input.clicked_tile();
+    let action = if input.wait_pressed() || clicked_tile == Some(destination.0) {
+        UnitAction::Wait
+    } else if let Some(pos) = clicked_tile {
+        let Some(target) = attack_target_param.get(selected_unit.0, destination.0, pos) else { return };
+        UnitAction::Attack {
+            target,
+            heavy: heavy_attack_held(&input.keys),
+        }
+    } else {
+        return;
+    };
+    turns.send(UnitTurn {
+        unit: selected_unit.0,
+        start_position: start_pos.0,
+        end_position: destination.0,
+        action,
+    });
+    commands.remove_resource::<MoveDestination>();
+    next_state.set(TRPGState::Battle);
+}
+
+fn cycle_atb_mode(mut atb_mode: ResMut<AtbMode>, keys: Res<Input<KeyCode>>) {
+    if keys.just_pressed(KeyCode::M) {
+        *atb_mode = atb_mode.next();
+        info!("ATB mode: {:?}", *atb_mode);
+    }
+}
+
+pub struct TurnInputPlugin;
+
+impl Plugin for TurnInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            (select_unit, auto_select_unit)
+                .distributive_run_if(no_unit_moving)
+                .in_set(OnUpdate(TRPGState::Battle)),
+        )
+        .add_systems((choose_move, mark_path_to_cursor).in_set(OnUpdate(TRPGState::ChoosingMove)))
+        .add_system(clear_path.in_schedule(OnExit(TRPGState::ChoosingMove)))
+        .add_system(choose_attack.in_set(OnUpdate(TRPGState::ChoosingAttack)))
+        .add_system(cycle_atb_mode.run_if(battle_in_progress));
+    }
+}
@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+use bevy_egui::{
+    egui::{self, Align2},
+    EguiContexts, EguiPlugin,
+};
+
+use crate::{
+    cursor::CursorPos,
+    logic::{
+        Equipment, GridPosition, MovementType, StatusEffects, Unit, UnitRange, UnitSpeed,
+        UnitStats, TICK_SECONDS,
+    },
+    TRPGState,
+};
+
+/// The unit whose card stays open while the cursor is elsewhere.
+#[derive(Resource, Default)]
+struct PinnedUnit(Option<Entity>);
+
+/// Right-clicking a unit pins its card, while right-clicking anything else
+/// unpins it. Only while no orders are being chosen, as right-click backs out
+/// of those.
+fn pin_unit_card(
+    mut pinned_unit: ResMut<PinnedUnit>,
+    units: Query<(Entity, &GridPosition), With<Unit>>,
+    buttons: Res<Input<MouseButton>>,
+    cursor: Res<CursorPos>,
+) {
+    if !buttons.just_pressed(MouseButton::Right) {
+        return;
+    }
+    let hovered = cursor.grid_position();
+    pinned_unit.0 = units
+        .iter()
+        .find(|(_, pos)| pos.0 == hovered)
+        .map(|(unit, _)| unit);
+}
+
+/// Shows the card of the unit under the cursor, or of the pinned unit.
+fn show_unit_card(
+    mut contexts: EguiContexts,
+    units: Query<(Entity, &GridPosition), With<Unit>>,
+    stats: Query<(&Unit, &UnitStats, &UnitSpeed, &MovementType, &UnitRange)>,
+    details: Query<(Option<&Name>, Option<&StatusEffects>, Option<&Equipment>)>,
+    pinned_unit: Res<PinnedUnit>,
+    cursor: Res<CursorPos>,
+) {
+    let hovered = cursor.grid_position();
+    let Some(card) = units
+        .iter()
+        .find(|(_, pos)| pos.0 == hovered)
+        .map(|(unit, _)| unit)
+        .or(pinned_unit.0) else { return };
+    let Ok((unit, unit_stats, unit_speed, movement_type, unit_range)) = stats.get(card) else { return };
+    let Ok((name, status_effects, equipment)) = details.get(card) else { return };
+    let ranges: Vec<_> = unit_range
+        .valid_ranges
+        .iter()
+        .map(|range| range.to_string())
+        .collect();
+    let status_effects: Vec<_> = status_effects
+        .iter()
+        .flat_map(|status_effects| &status_effects.0)
+        .map(|effect| format!("{} ({} turns)", effect.name, effect.turns_left))
+        .collect();
+    let equipment = equipment.cloned().unwrap_or_default();
+    let weapon = equipment.weapon.unwrap_or_else(|| "none".to_string());
+    let armor = equipment.armor.unwrap_or_else(|| "none".to_string());
+    let readiness = if unit.is_ready(unit_stats) {
+        "ready".to_string()
+    } else {
+        format!(
+            "ready in {:.1}s",
+            unit.ticks_until_ready(unit_stats) as f32 * TICK_SECONDS
+        )
+    };
+
+    egui::Window::new(name.map_or("Unit", |name| name.as_str()))
+        .id(egui::Id::new("unit_card"))
+        .anchor(Align2::CENTER_TOP, [0.0, 8.0])
+        .resizable(false)
+        .collapsible(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(format!("HP: {}/{}", unit.current_hp, unit_stats.max_hp));
+            ui.label(format!("Attack: {}", unit_stats.base_atk));
+            ui.label(format!("Armor: {}", unit_stats.base_armor));
+            ui.label(format!("Agility: {}", unit_stats.agility));
+            ui.label(format!("Move: {} ({movement_type:?})", unit_speed.0));
+            ui.label(format!("Range: {}", ranges.join(", ")));
+            ui.label(format!(
+                "Initiative: {:.1}/{:.1}, {readiness}",
+                unit.initiative, unit_stats.max_initiative
+            ));
+            ui.separator();
+            if status_effects.is_empty() {
+                ui.label("Status: normal");
+            } else {
+                ui.label(format!("Status: {}", status_effects.join(", ")));
+            }
+            ui.label(format!("Weapon: {weapon}"));
+            ui.label(format!("Armor: {armor}"));
+        });
+}
+
+pub struct UnitCardPlugin;
+
+impl Plugin for UnitCardPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugin(EguiPlugin);
+        }
+        app.init_resource::<PinnedUnit>()
+            .add_system(pin_unit_card.in_set(OnUpdate(TRPGState::Battle)))
+            .add_system(show_unit_card);
+    }
+}
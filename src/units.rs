@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::{ldtk::FieldValue, prelude::*};
+
+use crate::{
+    logic::{
+        DeathDrop, Equipment, Faction, GridPosition, MovementType, StatusEffects, Unit,
+        UnitLogicBundle, UnitRange, UnitSpeed, UnitStats,
+    },
+    GRID_SIZE,
+};
+
+pub const ENEMY_IDENTIFIERS: [&str; 8] = [
+    "ActiveEnemy",
+    "MageEnemy",
+    "StationaryEnemy",
+    "ActiveRangedEnemy",
+    "StationaryRangedEnemy",
+    "FlyingEnemy",
+    "StationaryBoss",
+    "ActiveBoss",
+];
+
+#[derive(Bundle)]
+struct EnemyBundle {
+    unit_logic_bundle: UnitLogicBundle,
+    sprite_bundle: SpriteBundle,
+    death_drop: DeathDrop,
+    equipment: Equipment,
+    status_effects: StatusEffects,
+    grid_coords: GridCoords,
+}
+
+impl LdtkEntity for EnemyBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        layer_instance: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        let identifier = &entity_instance.identifier;
+        let grid_coords = GridCoords::from_entity_info(entity_instance, layer_instance);
+        Self {
+            unit_logic_bundle: placed_enemy_logic_bundle(
+                entity_instance,
+                IVec2::new(grid_coords.x, grid_coords.y),
+            ),
+            sprite_bundle: enemy_sprite(identifier),
+            death_drop: death_drop(identifier),
+            equipment: equipment(identifier),
+            status_effects: StatusEffects::default(),
+            grid_coords,
+        }
+    }
+}
+
+fn get_int_field(entity_instance: &EntityInstance, identifier: &str) -> Option<i32> {
+    entity_instance
+        .field_instances
+        .iter()
+        .find(|field| field.identifier == identifier)
+        .and_then(|field| match field.value {
+            FieldValue::Int(value) => value,
+            _ => None,
+        })
+}
+
+fn get_enum_field<'a>(entity_instance: &'a EntityInstance, identifier: &str) -> Option<&'a str> {
+    entity_instance
+        .field_instances
+        .iter()
+        .find(|field| field.identifier == identifier)
+        .and_then(|field| match &field.value {
+            FieldValue::Enum(value) => value.as_deref(),
+            _ => None,
+        })
+}
+
+pub fn enemy_movement_type(identifier: &str) -> MovementType {
+    match identifier {
+        "FlyingEnemy" => MovementType::Flying,
+        _ => MovementType::Foot,
+    }
+}
+
+/// The logic bundle of an enemy before any field overrides from the level.
+fn enemy_logic_bundle(identifier: &str, position: IVec2) -> UnitLogicBundle {
+    let (max_hp, base_atk, base_armor, agility, speed, valid_ranges) = match identifier {
+        "MageEnemy" => (4, 3, 0, 9, 4, vec![1, 2]),
+        "StationaryEnemy" => (6, 2, 2, 8, 0, vec![1]),
+        "ActiveRangedEnemy" => (4, 2, 0, 10, 4, vec![2, 3]),
+        "StationaryRangedEnemy" => (4, 2, 1, 9, 0, vec![2, 3]),
+        "FlyingEnemy" => (4, 2, 0, 13, 6, vec![1]),
+        "StationaryBoss" => (15, 5, 3, 7, 0, vec![1, 2]),
+        "ActiveBoss" => (15, 5, 3, 7, 4, vec![1]),
+        _ => (5, 2, 1, 10, 4, vec![1]),
+    };
+    UnitLogicBundle {
+        unit: Unit {
+            initiative: 0.0,
+            current_hp: max_hp,
+        },
+        unit_stats: UnitStats {
+            max_hp,
+            max_initiative: 5.0,
+            base_atk,
+            base_armor,
+            agility,
+        },
+        unit_range: UnitRange { valid_ranges },
+        unit_speed: UnitSpeed(speed),
+        movement_type: enemy_movement_type(identifier),
+        grid_position: GridPosition(position),
+        faction: Faction::Enemy,
+    }
+}
+
+/// The logic bundle of an enemy placed in the level, on `position`, with its
+/// field overrides applied. An enemy stays put if either its type or its
+/// `EnemyBehavior` says it is stationary; `Mobile` can't make a stationary
+/// type move.
+fn placed_enemy_logic_bundle(entity_instance: &EntityInstance, position: IVec2) -> UnitLogicBundle {
+    let mut bundle = enemy_logic_bundle(&entity_instance.identifier, position);
+
+    match get_enum_field(entity_instance, "Enemytype") {
+        Some("Ranged") => bundle.unit_range.valid_ranges = vec![2, 3],
+        Some("Mage") => bundle.unit_range.valid_ranges = vec![1, 2],
+        Some("Melee") | Some("Boss") => bundle.unit_range.valid_ranges = vec![1],
+        Some("Flying") => {
+            bundle.unit_range.valid_ranges = vec![1];
+            bundle.movement_type = MovementType::Flying;
+        }
+        _ => {}
+    }
+    if let Some("Stationary") = get_enum_field(entity_instance, "EnemyBehavior") {
+        bundle.unit_speed.0 = 0;
+    }
+    if let Some(hp) = get_int_field(entity_instance, "HP") {
+        bundle.unit_stats.max_hp = hp.max(1) as u32;
+        bundle.unit.current_hp = bundle.unit_stats.max_hp;
+    }
+    if let Some(strength) = get_int_field(entity_instance, "Strength") {
+        bundle.unit_stats.base_atk = strength.max(0) as u32;
+    }
+    bundle
+}
+
+fn enemy_sprite(identifier: &str) -> SpriteBundle {
+    let color = match identifier {
+        "MageEnemy" => Color::rgb(0.5, 0.3, 0.7),
+        "ActiveRangedEnemy" | "StationaryRangedEnemy" => Color::rgb(0.7, 0.6, 0.2),
+        "FlyingEnemy" => Color::rgb(0.3, 0.6, 0.7),
+        "StationaryBoss" | "ActiveBoss" => Color::rgb(0.8, 0.1, 0.1),
+        _ => Color::rgb(0.7, 0.3, 0.3),
+    };
+    SpriteBundle {
+        transform: Transform::from_translation(Vec3::new(0.0, 0.0, 2.0)),
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::new(GRID_SIZE, GRID_SIZE)),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn death_drop(identifier: &str) -> DeathDrop {
+    match identifier {
+        "StationaryBoss" | "ActiveBoss" => DeathDrop::Loot {
+            item: "Boss Treasure".to_string(),
+        },
+        _ => DeathDrop::Corpse,
+    }
+}
+
+fn equipment(identifier: &str) -> Equipment {
+    match identifier {
+        "MageEnemy" => Equipment::new("Staff", None),
+        "ActiveRangedEnemy" | "StationaryRangedEnemy" => Equipment::new("Shortbow", None),
+        "FlyingEnemy" => Equipment::new("Talons", None),
+        "StationaryBoss" | "ActiveBoss" => Equipment::new("Greatsword", Some("Plate Armor")),
+        _ => Equipment::new("Rusty Sword", Some("Leather Armor")),
+    }
+}
+
+/// An enemy spawned outside of LDtk, e.g. as a reinforcement.
+pub fn enemy_bundle(identifier: &str, position: IVec2) -> impl Bundle {
+    (
+        Name::new(identifier.to_string()),
+        enemy_logic_bundle(identifier, position),
+        enemy_sprite(identifier),
+        death_drop(identifier),
+        equipment(identifier),
+        StatusEffects::default(),
+    )
+}
+
+pub struct UnitsPlugin;
+
+impl Plugin for UnitsPlugin {
+    fn build(&self, app: &mut App) {
+        for identifier in ENEMY_IDENTIFIERS {
+            app.register_ldtk_entity::<EnemyBundle>(identifier);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy_ecs_ldtk::ldtk::FieldInstance;
+
+    use super::*;
+
+    fn field(identifier: &str, value: FieldValue) -> FieldInstance {
+        FieldInstance {
+            identifier: identifier.to_string(),
+            tile: None,
+            field_instance_type: String::new(),
+            value,
+            def_uid: 0,
+            real_editor_values: vec![],
+        }
+    }
+
+    fn active_enemy(field_instances: Vec<FieldInstance>) -> EntityInstance {
+        EntityInstance {
+            identifier: "ActiveEnemy".to_string(),
+            field_instances,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn fields_override_enemy_stats() {
+        let entity_instance = active_enemy(vec![
+            field("Enemytype", FieldValue::Enum(Some("Ranged".to_string()))),
+            field(
+                "EnemyBehavior",
+                FieldValue::Enum(Some("Stationary".to_string())),
+            ),
+            field("HP", FieldValue::Int(Some(9))),
+            field("Strength", FieldValue::Int(Some(4))),
+        ]);
+        let bundle = placed_enemy_logic_bundle(&entity_instance, IVec2::new(7, 3));
+
+        assert_eq!(bundle.grid_position.0, IVec2::new(7, 3));
+        assert_eq!(bundle.unit_range.valid_ranges, vec![2, 3]);
+        assert_eq!(bundle.unit_speed.0, 0);
+        assert_eq!(bundle.unit_stats.max_hp, 9);
+        assert_eq!(bundle.unit.current_hp, 9);
+        assert_eq!(bundle.unit_stats.base_atk, 4);
+    }
+
+    #[test]
+    fn mobile_behavior_does_not_move_stationary_types() {
+        let entity_instance = EntityInstance {
+            identifier: "StationaryEnemy".to_string(),
+            field_instances: vec![field(
+                "EnemyBehavior",
+                FieldValue::Enum(Some("Mobile".to_string())),
+            )],
+            ..default()
+        };
+        let bundle = placed_enemy_logic_bundle(&entity_instance, IVec2::ZERO);
+        assert_eq!(bundle.unit_speed.0, 0);
+
+        let bundle = placed_enemy_logic_bundle(&active_enemy(vec![]), IVec2::ZERO);
+        assert!(bundle.unit_speed.0 > 0);
+    }
+}
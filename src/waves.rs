@@ -0,0 +1,266 @@
+use std::collections::HashSet;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_ecs_ldtk::{ldtk::FieldValue, prelude::*};
+
+use crate::{
+    logic::{
+        BattleClock, Faction, FactionRelations, GridPosition, LogicTileParam, MovementType,
+        PendingReinforcements, Unit,
+    },
+    units::{enemy_bundle, enemy_movement_type, ENEMY_IDENTIFIERS},
+    zone::{zone, Zone},
+    TRPGState,
+};
+
+/// Marks a zone reinforcements appear in.
+#[derive(Component, Default)]
+struct EnemySpawnZone;
+
+#[derive(Bundle, LdtkEntity)]
+struct EnemySpawnZoneBundle {
+    enemy_spawn_zone: EnemySpawnZone,
+    #[with(zone)]
+    zone: Zone,
+    #[grid_coords]
+    grid_coords: GridCoords,
+}
+
+/// Flipped once any unit stands on it.
+#[derive(Component, Default)]
+pub struct Switch {
+    pub flipped: bool,
+}
+
+#[derive(Bundle, LdtkEntity)]
+struct SwitchBundle {
+    switch: Switch,
+    #[grid_coords]
+    grid_coords: GridCoords,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum WaveTrigger {
+    /// At least this many logical ticks of the `BattleClock` have elapsed.
+    Ticks(u64),
+    /// At most this many enemies are left.
+    EnemiesRemaining(usize),
+    /// Any switch on the level has been flipped.
+    Switch,
+}
+
+#[derive(Debug, PartialEq)]
+struct Wave {
+    trigger: WaveTrigger,
+    enemies: Vec<String>,
+}
+
+/// Parses a wave written as `<trigger>: <EntityIdentifier>, ...`, where the
+/// trigger is `ticks <n>`, `enemies <n>` or `switch`.
+fn parse_wave(definition: &str) -> Option<Wave> {
+    let (trigger, enemies) = definition.split_once(':')?;
+    let trigger = match trigger.split_whitespace().collect::<Vec<_>>()[..] {
+        ["ticks", ticks] => WaveTrigger::Ticks(ticks.parse().ok()?),
+        ["enemies", enemies] => WaveTrigger::EnemiesRemaining(enemies.parse().ok()?),
+        ["switch"] => WaveTrigger::Switch,
+        _ => return None,
+    };
+    let enemies = enemies
+        .split(',')
+        .map(str::trim)
+        .filter(|enemy| !enemy.is_empty())
+        .map(|enemy| {
+            ENEMY_IDENTIFIERS
+                .contains(&enemy)
+                .then(|| enemy.to_string())
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(Wave { trigger, enemies })
+}
+
+/// Waves of the current level that haven't spawned yet.
+#[derive(Resource, Default)]
+struct PendingWaves(Vec<Wave>);
+
+/// Enemies whose wave has arrived but who had no free tile to spawn on yet.
+/// They spawn as soon as one frees up.
+#[derive(Resource, Default)]
+struct QueuedEnemies(Vec<String>);
+
+fn load_level_waves(
+    mut level_events: EventReader<LevelEvent>,
+    mut pending_waves: ResMut<PendingWaves>,
+    mut queued_enemies: ResMut<QueuedEnemies>,
+    levels: Query<&Handle<LdtkLevel>>,
+    level_assets: Res<Assets<LdtkLevel>>,
+) {
+    for level_event in level_events.iter() {
+        let LevelEvent::Spawned(iid) = level_event else { continue };
+        let Some(level) = levels
+            .iter()
+            .filter_map(|handle| level_assets.get(handle))
+            .find(|ldtk_level| &ldtk_level.level.iid == iid) else { continue };
+        pending_waves.0.clear();
+        queued_enemies.0.clear();
+        for field_instance in level.level.field_instances.iter() {
+            let FieldValue::Strings(definitions) = &field_instance.value else { continue };
+            if field_instance.identifier != "Waves" {
+                continue;
+            }
+            for definition in definitions.iter().flatten() {
+                match parse_wave(definition) {
+                    Some(wave) => pending_waves.0.push(wave),
+                    None => warn!("Ignoring invalid wave definition {definition:?}"),
+                }
+            }
+        }
+    }
+}
+
+fn flip_switches(
+    mut switches: Query<(&mut Switch, &GridCoords)>,
+    units: Query<&GridPosition, With<Unit>>,
+) {
+    for (mut switch, grid_coords) in switches.iter_mut() {
+        let pos = IVec2::new(grid_coords.x, grid_coords.y);
+        if !switch.flipped && units.iter().any(|unit_pos| unit_pos.0 == pos) {
+            switch.flipped = true;
+        }
+    }
+}
+
+#[derive(SystemParam)]
+struct WaveTriggerParam<'w, 's> {
+    clock: Res<'w, BattleClock>,
+    factions: Query<'w, 's, &'static Faction, With<Unit>>,
+    relations: Res<'w, FactionRelations>,
+    switches: Query<'w, 's, &'static Switch>,
+}
+
+impl<'w, 's> WaveTriggerParam<'w, 's> {
+    fn is_triggered(&self, trigger: &WaveTrigger) -> bool {
+        match *trigger {
+            WaveTrigger::Ticks(ticks) => self.clock.ticks >= ticks,
+            WaveTrigger::EnemiesRemaining(count) => {
+                self.factions
+                    .iter()
+                    .filter(|&&faction| self.relations.is_hostile(Faction::Player, faction))
+                    .count()
+                    <= count
+            }
+            WaveTrigger::Switch => self.switches.iter().any(|switch| switch.flipped),
+        }
+    }
+}
+
+#[derive(SystemParam)]
+struct SpawnTileParam<'w, 's> {
+    zones: Query<'w, 's, (&'static Zone, &'static GridCoords), With<EnemySpawnZone>>,
+    units: Query<'w, 's, &'static GridPosition, With<Unit>>,
+    logic_tile_param: LogicTileParam<'w, 's>,
+}
+
+impl<'w, 's> SpawnTileParam<'w, 's> {
+    fn occupied(&self) -> HashSet<IVec2> {
+        self.units.iter().map(|pos| pos.0).collect()
+    }
+
+    /// Spawn zone tiles a unit moving by `movement_type` can stand on,
+    /// occupied or not.
+    fn spawn_tiles(&self, movement_type: MovementType) -> impl Iterator<Item = IVec2> + '_ {
+        self.zones
+            .iter()
+            .flat_map(|(zone, origin)| zone.tiles(origin))
+            .filter(move |pos| self.logic_tile_param.can_move(*pos, movement_type))
+    }
+}
+
+fn spawn_waves(
+    mut commands: Commands,
+    mut pending_waves: ResMut<PendingWaves>,
+    mut queued_enemies: ResMut<QueuedEnemies>,
+    mut pending_reinforcements: ResMut<PendingReinforcements>,
+    wave_trigger_param: WaveTriggerParam,
+    spawn_tile_param: SpawnTileParam,
+) {
+    // Counted before spawning, as enemies spawned here only show up once
+    // commands are applied.
+    pending_reinforcements.0 = pending_waves
+        .0
+        .iter()
+        .map(|wave| wave.enemies.len())
+        .sum::<usize>()
+        + queued_enemies.0.len();
+    let (triggered, pending): (Vec<_>, Vec<_>) = pending_waves
+        .0
+        .drain(..)
+        .partition(|wave| wave_trigger_param.is_triggered(&wave.trigger));
+    pending_waves.0 = pending;
+    queued_enemies
+        .0
+        .extend(triggered.into_iter().flat_map(|wave| wave.enemies));
+    if queued_enemies.0.is_empty() {
+        return;
+    }
+
+    let mut occupied = spawn_tile_param.occupied();
+    queued_enemies.0.retain(|enemy| {
+        let Some(pos) = spawn_tile_param
+            .spawn_tiles(enemy_movement_type(enemy))
+            .find(|pos| !occupied.contains(pos)) else { return true };
+        occupied.insert(pos);
+        commands.spawn(enemy_bundle(enemy, pos));
+        false
+    });
+}
+
+pub struct WavesPlugin;
+
+impl Plugin for WavesPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<EnemySpawnZoneBundle>("EnemySpawnZone")
+            .register_ldtk_entity::<SwitchBundle>("Switch")
+            .init_resource::<PendingWaves>()
+            .init_resource::<QueuedEnemies>()
+            .add_system(load_level_waves)
+            .add_systems((flip_switches, spawn_waves).in_set(OnUpdate(TRPGState::Battle)));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_wave, Wave, WaveTrigger};
+
+    #[test]
+    fn parse_wave_reads_each_trigger() {
+        assert_eq!(
+            parse_wave("ticks 15: ActiveEnemy, FlyingEnemy"),
+            Some(Wave {
+                trigger: WaveTrigger::Ticks(15),
+                enemies: vec!["ActiveEnemy".to_string(), "FlyingEnemy".to_string()],
+            })
+        );
+        assert_eq!(
+            parse_wave("enemies 3: MageEnemy"),
+            Some(Wave {
+                trigger: WaveTrigger::EnemiesRemaining(3),
+                enemies: vec!["MageEnemy".to_string()],
+            })
+        );
+        assert_eq!(
+            parse_wave("switch: ActiveBoss"),
+            Some(Wave {
+                trigger: WaveTrigger::Switch,
+                enemies: vec!["ActiveBoss".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn parse_wave_rejects_invalid_definitions() {
+        assert_eq!(parse_wave("ActiveEnemy"), None);
+        assert_eq!(parse_wave("ticks: ActiveEnemy"), None);
+        assert_eq!(parse_wave("soon: ActiveEnemy"), None);
+        assert_eq!(parse_wave("switch: Dragon"), None);
+    }
+}
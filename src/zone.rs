@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::GRID_SIZE;
+
+/// A rectangle of tiles placed in LDtk. `GridCoords` on the same entity is the
+/// zone's top left tile, as LDtk places entities by their top left corner.
+#[derive(Component)]
+pub struct Zone {
+    size: IVec2,
+}
+
+impl Zone {
+    pub fn contains(&self, origin: &GridCoords, pos: IVec2) -> bool {
+        pos.x >= origin.x
+            && pos.x < origin.x + self.size.x
+            && pos.y <= origin.y
+            && pos.y > origin.y - self.size.y
+    }
+
+    pub fn tiles(&self, origin: &GridCoords) -> impl Iterator<Item = IVec2> {
+        let origin = IVec2::new(origin.x, origin.y);
+        let size = self.size;
+        (0..size.y)
+            .flat_map(move |y| (0..size.x).map(move |x| IVec2::new(origin.x + x, origin.y - y)))
+    }
+}
+
+pub fn zone(entity_instance: &EntityInstance) -> Zone {
+    Zone {
+        size: IVec2::new(entity_instance.width, entity_instance.height) / GRID_SIZE as i32,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy_ecs_ldtk::utils::ldtk_grid_coords_to_grid_coords;
+
+    use super::*;
+
+    /// The enemy spawn zone of the first level: 3 by 2 tiles placed at [7,3]
+    /// on a 16 tile high level.
+    fn enemy_spawn_zone() -> (Zone, GridCoords) {
+        let entity_instance = EntityInstance {
+            width: 48,
+            height: 32,
+            ..default()
+        };
+        (
+            zone(&entity_instance),
+            ldtk_grid_coords_to_grid_coords(IVec2::new(7, 3), 16),
+        )
+    }
+
+    #[test]
+    fn contains_tiles_below_and_right_of_origin() {
+        let (zone, origin) = enemy_spawn_zone();
+        assert!(zone.contains(&origin, IVec2::new(7, 12)));
+        assert!(zone.contains(&origin, IVec2::new(9, 11)));
+        assert!(!zone.contains(&origin, IVec2::new(6, 12)));
+        assert!(!zone.contains(&origin, IVec2::new(10, 12)));
+        assert!(!zone.contains(&origin, IVec2::new(7, 13)));
+        assert!(!zone.contains(&origin, IVec2::new(7, 10)));
+    }
+
+    #[test]
+    fn tiles_cover_the_zone() {
+        let (zone, origin) = enemy_spawn_zone();
+        let mut tiles: Vec<_> = zone.tiles(&origin).collect();
+        tiles.sort_by_key(|tile| (tile.x, tile.y));
+        let expected: Vec<_> = (7..=9)
+            .flat_map(|x| (11..=12).map(move |y| IVec2::new(x, y)))
+            .collect();
+        assert_eq!(tiles, expected);
+        assert!(tiles.iter().all(|&tile| zone.contains(&origin, tile)));
+    }
+}